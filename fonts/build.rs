@@ -0,0 +1,419 @@
+//! Converts the BDF fonts in `bdf/` into tables embedded-graphics can draw from.
+//!
+//! Monospaced fonts become a [`MonoFont`] with its glyphs on a grid, proportional ones a
+//! `ProportionalFont` with its glyphs side by side. To bundle another font, drop its BDF
+//! file in `bdf/` and add it to [`FONTS`].
+//!
+//! BDF has no kerning, so a proportional font can come with a `.kern` file of the same
+//! name listing pairs of characters and the pixels to move the second one by.
+//!
+//! [`MonoFont`]: https://docs.rs/embedded-graphics/0.8/embedded_graphics/mono_font/struct.MonoFont.html
+
+use std::env;
+use std::fmt::Write as _;
+use std::fs;
+use std::path::Path;
+
+/// The constant each font is generated as, its BDF file and how its glyphs are spaced.
+const FONTS: &[(&str, &str, Spacing)] = &[
+    ("FONT_3X5", "bdf/3x5.bdf", Spacing::Mono),
+    ("FONT_4X7", "bdf/4x7.bdf", Spacing::Mono),
+    ("FONT_PROP_8", "bdf/prop8.bdf", Spacing::Proportional),
+    ("FONT_DIGITS_16", "bdf/digits16.bdf", Spacing::Proportional),
+];
+
+/// Monospaced glyphs are laid out this many to a row of the font image.
+const GLYPHS_PER_ROW: u32 = 16;
+
+#[derive(Clone, Copy)]
+enum Spacing {
+    Mono,
+    Proportional,
+}
+
+/// The parts of a BDF font the tables are built from.
+struct Font {
+    comments: Vec<String>,
+    /// Width, height and offset from the origin of a box around every glyph.
+    bounding_box: (u32, u32, i32, i32),
+    ascent: u32,
+    descent: u32,
+    default_char: Option<u32>,
+    glyphs: Vec<Glyph>,
+}
+
+struct Glyph {
+    encoding: u32,
+    /// How far the pen moves on after drawing the glyph.
+    advance: u32,
+    width: u32,
+    height: u32,
+    x_offset: i32,
+    /// From the baseline up to the bottom of the glyph.
+    y_offset: i32,
+    rows: Vec<Vec<bool>>,
+}
+
+impl Glyph {
+    fn lit(&self, x: u32, y: u32) -> bool {
+        self.rows[y as usize][x as usize]
+    }
+}
+
+fn main() {
+    let out = Path::new(&env::var("OUT_DIR").unwrap()).join("fonts.rs");
+    let mut code = String::new();
+
+    for (name, path, spacing) in FONTS {
+        println!("cargo:rerun-if-changed={path}");
+        let source = fs::read_to_string(path).unwrap_or_else(|e| panic!("{path}: {e}"));
+        let font = parse(&source).unwrap_or_else(|e| panic!("{path}: {e}"));
+
+        match spacing {
+            Spacing::Mono => {
+                let advance = mono_advance(&font).unwrap_or_else(|e| panic!("{path}: {e}"));
+                write_mono(&mut code, name, &font, advance)
+            }
+            Spacing::Proportional => {
+                let kern = Path::new(path).with_extension("kern");
+                println!("cargo:rerun-if-changed={}", kern.display());
+                let kerning = match fs::read_to_string(&kern) {
+                    Ok(source) => {
+                        parse_kerning(&source).unwrap_or_else(|e| panic!("{}: {e}", kern.display()))
+                    }
+                    Err(_) => Vec::new(),
+                };
+                write_proportional(&mut code, name, &font, &kerning)
+            }
+        }
+        .unwrap();
+    }
+
+    fs::write(out, code).unwrap();
+}
+
+fn parse(source: &str) -> Result<Font, String> {
+    let mut font = Font {
+        comments: Vec::new(),
+        bounding_box: (0, 0, 0, 0),
+        ascent: 0,
+        descent: 0,
+        default_char: None,
+        glyphs: Vec::new(),
+    };
+    let mut lines = source.lines().enumerate();
+    let mut glyph: Option<Glyph> = None;
+    // Name of the glyph being read, to say where errors are
+    let mut name: Option<String> = None;
+
+    while let Some((number, line)) = lines.next() {
+        let at = |number: usize| match &name {
+            Some(name) => format!("line {} (glyph {name})", number + 1),
+            None => format!("line {}", number + 1),
+        };
+        let fail = |what: &str| format!("{}: {what}", at(number));
+        let mut words = line.split_whitespace();
+        let keyword = words.next().unwrap_or_default();
+        let numbers = || -> Result<Vec<i32>, String> {
+            line.split_whitespace()
+                .skip(1)
+                .map(|word| word.parse().map_err(|_| fail("expected numbers")))
+                .collect()
+        };
+        let first = || -> Result<i32, String> {
+            numbers()?
+                .first()
+                .copied()
+                .ok_or_else(|| fail(&format!("{keyword} needs a number")))
+        };
+
+        match (keyword, glyph.as_mut()) {
+            ("COMMENT", None) => {
+                font.comments
+                    .push(line.trim_start_matches("COMMENT").trim().to_string());
+            }
+            ("FONTBOUNDINGBOX", None) => {
+                let [width, height, x, y] = numbers()?[..] else {
+                    return Err(fail("FONTBOUNDINGBOX needs four numbers"));
+                };
+                font.bounding_box = (width as u32, height as u32, x, y);
+            }
+            ("FONT_ASCENT", None) => font.ascent = first()? as u32,
+            ("FONT_DESCENT", None) => font.descent = first()? as u32,
+            ("DEFAULT_CHAR", None) => font.default_char = Some(first()? as u32),
+            ("STARTCHAR", None) => {
+                name = Some(line.trim_start_matches("STARTCHAR").trim().to_string());
+                glyph = Some(Glyph {
+                    encoding: 0,
+                    advance: 0,
+                    width: 0,
+                    height: 0,
+                    x_offset: 0,
+                    y_offset: 0,
+                    rows: Vec::new(),
+                });
+            }
+            ("ENCODING", Some(glyph)) => glyph.encoding = first()? as u32,
+            ("DWIDTH", Some(glyph)) => glyph.advance = first()? as u32,
+            ("BBX", Some(glyph)) => {
+                let [width, height, x, y] = numbers()?[..] else {
+                    return Err(fail("BBX needs four numbers"));
+                };
+                (glyph.width, glyph.height) = (width as u32, height as u32);
+                (glyph.x_offset, glyph.y_offset) = (x, y);
+            }
+            ("BITMAP", Some(glyph)) => {
+                for _ in 0..glyph.height {
+                    let (number, hex) = lines.next().ok_or_else(|| fail("bitmap cut short"))?;
+                    let bytes = (0..hex.trim().len() / 2)
+                        .map(|i| u8::from_str_radix(&hex.trim()[i * 2..i * 2 + 2], 16))
+                        .collect::<Result<Vec<_>, _>>()
+                        .map_err(|_| format!("{}: bad bitmap row", at(number)))?;
+                    let row = (0..glyph.width as usize)
+                        .map(|x| {
+                            bytes
+                                .get(x / 8)
+                                .is_some_and(|byte| byte & 0x80 >> (x % 8) != 0)
+                        })
+                        .collect();
+                    glyph.rows.push(row);
+                }
+            }
+            ("ENDCHAR", Some(_)) => {
+                let glyph = glyph.take().unwrap();
+                name = None;
+                // Unencoded glyphs have nothing to map them from
+                if glyph.encoding != u32::MAX && char::from_u32(glyph.encoding).is_some() {
+                    font.glyphs.push(glyph);
+                }
+            }
+            ("STARTCHAR", Some(_)) => return Err(fail("STARTCHAR before ENDCHAR")),
+            _ => {}
+        }
+    }
+
+    if font.ascent + font.descent == 0 {
+        return Err("FONT_ASCENT and FONT_DESCENT are missing".to_string());
+    }
+    font.glyphs.sort_by_key(|glyph| glyph.encoding);
+    font.glyphs.dedup_by_key(|glyph| glyph.encoding);
+    Ok(font)
+}
+
+/// Lines of a left character, a right one and a signed adjustment, `#` starting a comment.
+fn parse_kerning(source: &str) -> Result<Vec<(char, char, i32)>, String> {
+    let mut pairs = Vec::new();
+
+    for (number, line) in source.lines().enumerate() {
+        let line = line.split('#').next().unwrap_or_default();
+        let words: Vec<_> = line.split_whitespace().collect();
+        let single = |word: &str| {
+            let mut chars = word.chars();
+            chars.next().filter(|_| chars.next().is_none())
+        };
+
+        match words[..] {
+            [] => {}
+            [left, right, adjust] => pairs.push((
+                single(left).ok_or_else(|| format!("line {}: {left}", number + 1))?,
+                single(right).ok_or_else(|| format!("line {}: {right}", number + 1))?,
+                adjust
+                    .parse()
+                    .map_err(|_| format!("line {}: {adjust}", number + 1))?,
+            )),
+            _ => {
+                return Err(format!(
+                    "line {}: expected two characters and a number",
+                    number + 1
+                ))
+            }
+        }
+    }
+
+    pairs.sort_by_key(|(left, right, _)| (*left, *right));
+    Ok(pairs)
+}
+
+/// A 1 bit image packed the way `ImageRaw` reads it: rows padded to whole bytes, most
+/// significant bit first.
+struct Bitmap {
+    width: u32,
+    bytes: Vec<u8>,
+}
+
+impl Bitmap {
+    fn new(width: u32, height: u32) -> Self {
+        Bitmap {
+            width,
+            bytes: vec![0; (width.div_ceil(8) * height) as usize],
+        }
+    }
+
+    /// Copies `glyph` into the `cell` pixels wide, font high cell at `x`, `y`, its bitmap
+    /// `left` pixels in from the cell's left edge. Pixels outside the cell are dropped.
+    fn draw(&mut self, font: &Font, glyph: &Glyph, (x, y): (u32, u32), left: i32, cell: u32) {
+        let top = font.ascent as i32 - glyph.y_offset - glyph.height as i32;
+
+        for row in 0..glyph.height {
+            for column in 0..glyph.width {
+                let (cx, cy) = (left + column as i32, top + row as i32);
+                let inside = (0..cell as i32).contains(&cx)
+                    && (0..(font.ascent + font.descent) as i32).contains(&cy);
+                if inside && glyph.lit(column, row) {
+                    let (px, py) = (x + cx as u32, y + cy as u32);
+                    self.bytes[(py * self.width.div_ceil(8) + px / 8) as usize] |= 0x80 >> (px % 8);
+                }
+            }
+        }
+    }
+
+    fn write(&self, code: &mut String) -> std::fmt::Result {
+        write!(code, "ImageRaw::new(&[")?;
+        for (i, byte) in self.bytes.iter().enumerate() {
+            if i % 16 == 0 {
+                write!(code, "\n        ")?;
+            }
+            write!(code, "0x{byte:02x}, ")?;
+        }
+        write!(code, "\n    ], {})", self.width)
+    }
+}
+
+fn write_doc(code: &mut String, font: &Font) -> std::fmt::Result {
+    for comment in &font.comments {
+        writeln!(code, "/// {comment}")?;
+    }
+    Ok(())
+}
+
+/// The glyph drawn in place of characters the font doesn't have: its default
+/// character, else `?`, else the first one.
+fn replacement(font: &Font) -> usize {
+    [font.default_char, Some('?' as u32)]
+        .into_iter()
+        .flatten()
+        .find_map(|c| font.glyphs.iter().position(|glyph| glyph.encoding == c))
+        .unwrap_or(0)
+}
+
+/// How far apart the glyphs of a monospaced font are, the same for all of them.
+fn mono_advance(font: &Font) -> Result<u32, String> {
+    let advance = font
+        .glyphs
+        .first()
+        .map_or(font.bounding_box.0, |glyph| glyph.advance);
+
+    match font.glyphs.iter().find(|glyph| glyph.advance != advance) {
+        Some(glyph) => Err(format!(
+            "glyph {} is {} wide, not {advance} like the rest",
+            glyph.encoding, glyph.advance
+        )),
+        None => Ok(advance),
+    }
+}
+
+fn write_mono(code: &mut String, name: &str, font: &Font, advance: u32) -> std::fmt::Result {
+    let (width, height) = (font.bounding_box.0, font.ascent + font.descent);
+    let count = font.glyphs.len() as u32;
+    let mut image = Bitmap::new(
+        width * GLYPHS_PER_ROW.min(count),
+        height * count.div_ceil(GLYPHS_PER_ROW),
+    );
+    for (index, glyph) in font.glyphs.iter().enumerate() {
+        let index = index as u32;
+        let (column, row) = (index % GLYPHS_PER_ROW, index / GLYPHS_PER_ROW);
+        let left = glyph.x_offset - font.bounding_box.2;
+        image.draw(font, glyph, (column * width, row * height), left, width);
+    }
+
+    // Runs of consecutive characters are written as ranges, "\0" then the first and last
+    let mut mapping = String::new();
+    for run in font
+        .glyphs
+        .chunk_by(|a, b| a.encoding + 1 == b.encoding)
+        .map(|run| (run[0].encoding, run[run.len() - 1].encoding))
+    {
+        let (first, last) = (
+            char::from_u32(run.0).unwrap(),
+            char::from_u32(run.1).unwrap(),
+        );
+        match run.1 - run.0 {
+            0 => mapping.push(first),
+            1 => mapping.extend([first, last]),
+            _ => mapping.extend(['\0', first, last]),
+        }
+    }
+
+    write_doc(code, font)?;
+    writeln!(code, "pub const {name}: MonoFont<'static> = MonoFont {{")?;
+    write!(code, "    image: ")?;
+    image.write(code)?;
+    writeln!(code, ",")?;
+    writeln!(
+        code,
+        "    glyph_mapping: &StrGlyphMapping::new({mapping:?}, {}),",
+        replacement(font)
+    )?;
+    writeln!(code, "    character_size: Size::new({width}, {height}),")?;
+    writeln!(
+        code,
+        "    character_spacing: {},",
+        advance.saturating_sub(width)
+    )?;
+    writeln!(code, "    baseline: {},", font.ascent - 1)?;
+    writeln!(
+        code,
+        "    strikethrough: DecorationDimensions::new({}, 1),",
+        font.ascent / 2
+    )?;
+    writeln!(
+        code,
+        "    underline: DecorationDimensions::new({}, 1),",
+        font.ascent.min(height - 1)
+    )?;
+    writeln!(code, "}};\n")
+}
+
+fn write_proportional(
+    code: &mut String,
+    name: &str,
+    font: &Font,
+    kerning: &[(char, char, i32)],
+) -> std::fmt::Result {
+    let height = font.ascent + font.descent;
+    // Each glyph takes as many columns as it reaches right of the origin
+    let widths: Vec<u32> = font
+        .glyphs
+        .iter()
+        .map(|glyph| glyph.x_offset.max(0) as u32 + glyph.width)
+        .collect();
+
+    let mut image = Bitmap::new(widths.iter().sum::<u32>().max(1), height);
+    let mut x = 0;
+    let mut glyphs = String::new();
+    for (glyph, width) in font.glyphs.iter().zip(&widths) {
+        image.draw(font, glyph, (x, 0), glyph.x_offset.max(0), *width);
+        writeln!(
+            glyphs,
+            "        Glyph::new({:?}, {x}, {width}, {}),",
+            char::from_u32(glyph.encoding).unwrap(),
+            glyph.advance
+        )?;
+        x += width;
+    }
+
+    write_doc(code, font)?;
+    writeln!(
+        code,
+        "pub const {name}: ProportionalFont<'static> = ProportionalFont {{"
+    )?;
+    write!(code, "    image: ")?;
+    image.write(code)?;
+    writeln!(code, ",")?;
+    writeln!(code, "    glyphs: &[\n{glyphs}    ],")?;
+    writeln!(code, "    kerning: &{kerning:?},")?;
+    writeln!(code, "    height: {height},")?;
+    writeln!(code, "    baseline: {},", font.ascent - 1)?;
+    writeln!(code, "    replacement: {},", replacement(font))?;
+    writeln!(code, "}};\n")
+}
@@ -0,0 +1,106 @@
+//! Narrow bitmap fonts for 8 pixel high LED rows, with transport icons.
+//!
+//! The fonts are drawn as BDF files in `bdf/` and turned into tables by the build
+//! script. [`FONT_3X5`] and [`FONT_4X7`] are [`MonoFont`]s and work with
+//! [`MonoTextStyle`](embedded_graphics::mono_font::MonoTextStyle) like the fonts built
+//! into embedded-graphics. [`FONT_PROP_8`] gives every glyph its own width and is drawn
+//! with [`ProportionalTextStyle`], as is [`FONT_DIGITS_16`], which only has the digits
+//! and a colon for a big clock.
+//!
+//! The three text fonts have the icons below, which can be put in any string:
+//!
+//! ```
+//! let line = format!("{} 46A", matrix_fonts::BUS);
+//! ```
+#![no_std]
+
+use embedded_graphics::image::{ImageRaw, SubImage};
+use embedded_graphics::mono_font::mapping::StrGlyphMapping;
+use embedded_graphics::mono_font::{DecorationDimensions, MonoFont};
+use embedded_graphics::pixelcolor::BinaryColor;
+use embedded_graphics::prelude::{ImageDrawableExt, Point, Size};
+use embedded_graphics::primitives::Rectangle;
+
+mod style;
+
+pub use style::{ProportionalTextStyle, FIGURE_SPACE};
+
+pub const BUS: char = '\u{E000}';
+pub const TRAIN: char = '\u{E001}';
+pub const TRAM: char = '\u{E002}';
+pub const CLOCK: char = '\u{E003}';
+pub const WARNING: char = '\u{E004}';
+pub const WALK: char = '\u{E005}';
+
+include!(concat!(env!("OUT_DIR"), "/fonts.rs"));
+
+/// Where a glyph of a [`ProportionalFont`] sits in the font image, and how far the
+/// next one starts after it.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Glyph {
+    pub character: char,
+    pub x: u32,
+    pub width: u32,
+    pub advance: u32,
+}
+
+impl Glyph {
+    pub const fn new(character: char, x: u32, width: u32, advance: u32) -> Self {
+        Glyph {
+            character,
+            x,
+            width,
+            advance,
+        }
+    }
+}
+
+/// A bitmap font whose glyphs are only as wide as they need to be.
+#[derive(Clone, Copy, Debug)]
+pub struct ProportionalFont<'a> {
+    /// Every glyph side by side, each the full height of the font.
+    pub image: ImageRaw<'a, BinaryColor>,
+    /// Sorted by character.
+    pub glyphs: &'a [Glyph],
+    /// Pixels to move the right character of a pair by, sorted by pair.
+    pub kerning: &'a [(char, char, i32)],
+    pub height: u32,
+    /// Offset from the top of the glyphs to the baseline.
+    pub baseline: u32,
+    /// Index in `glyphs` of the glyph drawn for characters the font doesn't have.
+    pub replacement: usize,
+}
+
+impl<'a> ProportionalFont<'a> {
+    /// The glyph drawn for `c`.
+    pub fn glyph(&self, c: char) -> &Glyph {
+        let index = self
+            .glyphs
+            .binary_search_by_key(&c, |glyph| glyph.character)
+            .unwrap_or(self.replacement);
+        &self.glyphs[index]
+    }
+
+    /// How far to move `right` when it follows `left`, on top of `left`'s advance.
+    pub fn kerning(&self, left: char, right: char) -> i32 {
+        self.kerning
+            .binary_search_by_key(&(left, right), |(l, r, _)| (*l, *r))
+            .map_or(0, |index| self.kerning[index].2)
+    }
+
+    /// The advance of the widest digit, which all digits take with tabular figures.
+    pub fn tabular_advance(&self) -> u32 {
+        ('0'..='9')
+            .map(|digit| self.glyph(digit).advance)
+            .max()
+            .unwrap_or_default()
+    }
+
+    /// The pixels of `glyph`, to draw with its top left corner at the pen position.
+    pub fn glyph_image(&self, glyph: &Glyph) -> SubImage<'_, ImageRaw<'a, BinaryColor>> {
+        self.image.sub_image(&Rectangle::new(
+            Point::new(glyph.x as i32, 0),
+            Size::new(glyph.width, self.height),
+        ))
+    }
+}
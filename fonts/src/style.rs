@@ -0,0 +1,242 @@
+use crate::{Glyph, ProportionalFont};
+use embedded_graphics::image::GetPixel;
+use embedded_graphics::pixelcolor::BinaryColor;
+use embedded_graphics::prelude::{DrawTarget, PixelColor, Point, Size};
+use embedded_graphics::primitives::Rectangle;
+use embedded_graphics::text::renderer::{CharacterStyle, TextMetrics, TextRenderer};
+use embedded_graphics::text::Baseline;
+use embedded_graphics::Pixel;
+
+/// A blank as wide as a digit, for padding numbers to line up with tabular digits.
+pub const FIGURE_SPACE: char = '\u{2007}';
+
+/// Text style for a [`ProportionalFont`], for use with
+/// [`Text`](embedded_graphics::text::Text) anywhere a `MonoTextStyle` would go.
+///
+/// Every character moves the pen on by its own advance, adjusted by the font's kerning.
+/// With tabular digits, all digits take the width of the widest one, so numbers of the
+/// same length line up; [`FIGURE_SPACE`] pads shorter ones.
+#[derive(Clone, Copy, Debug)]
+pub struct ProportionalTextStyle<'a, C> {
+    font: &'a ProportionalFont<'a>,
+    text_color: Option<C>,
+    background_color: Option<C>,
+    kerning: bool,
+    tabular_digits: bool,
+}
+
+/// Where one character of a string goes, in pixels from the start of the string.
+struct Placement<'a> {
+    /// `None` for a figure space the font has no glyph for.
+    glyph: Option<&'a Glyph>,
+    /// Start of the character's advance.
+    start: i32,
+    /// Left edge of the glyph, which is centered in the advance for tabular digits.
+    x: i32,
+    advance: u32,
+}
+
+impl<'a, C> ProportionalTextStyle<'a, C>
+where
+    C: PixelColor,
+{
+    /// Kerned text with proportional digits and a transparent background.
+    pub const fn new(font: &'a ProportionalFont<'a>, text_color: C) -> Self {
+        ProportionalTextStyle {
+            font,
+            text_color: Some(text_color),
+            background_color: None,
+            kerning: true,
+            tabular_digits: false,
+        }
+    }
+
+    pub const fn with_kerning(mut self, kerning: bool) -> Self {
+        self.kerning = kerning;
+        self
+    }
+
+    /// Gives every digit the same width, so counts like "1m" and "10m" line up when right
+    /// aligned.
+    pub const fn with_tabular_digits(mut self, tabular_digits: bool) -> Self {
+        self.tabular_digits = tabular_digits;
+        self
+    }
+
+    pub fn font(&self) -> &'a ProportionalFont<'a> {
+        self.font
+    }
+
+    fn tabular(&self, c: char) -> bool {
+        c == FIGURE_SPACE || self.tabular_digits && c.is_ascii_digit()
+    }
+
+    fn placements<'t>(&'t self, text: &'t str) -> impl Iterator<Item = Placement<'a>> + 't {
+        let font = self.font;
+        let tabular_advance = font.tabular_advance();
+        let mut pen = 0;
+        let mut previous: Option<char> = None;
+
+        text.chars().map(move |c| {
+            let glyph = match c {
+                FIGURE_SPACE => font
+                    .glyphs
+                    .binary_search_by_key(&c, |glyph| glyph.character)
+                    .ok()
+                    .map(|index| &font.glyphs[index]),
+                _ => Some(font.glyph(c)),
+            };
+
+            // Kerning would knock tabular digits out of line
+            if let Some(left) = previous.filter(|left| !self.tabular(*left) && !self.tabular(c)) {
+                if self.kerning {
+                    pen += font.kerning(left, c);
+                }
+            }
+
+            let advance = match glyph {
+                Some(glyph) if !self.tabular(c) => glyph.advance,
+                _ => tabular_advance,
+            };
+            let x = pen + glyph.map_or(0, |glyph| (advance as i32 - glyph.advance as i32) / 2);
+            let placement = Placement {
+                glyph,
+                start: pen,
+                x,
+                advance,
+            };
+
+            pen += advance as i32;
+            previous = Some(c);
+            placement
+        })
+    }
+
+    fn baseline_offset(&self, baseline: Baseline) -> i32 {
+        let height = self.font.height.saturating_sub(1);
+        match baseline {
+            Baseline::Top => 0,
+            Baseline::Bottom => height as i32,
+            Baseline::Middle => (height / 2) as i32,
+            Baseline::Alphabetic => self.font.baseline as i32,
+        }
+    }
+
+    fn fill_background<D>(&self, area: Rectangle, target: &mut D) -> Result<(), D::Error>
+    where
+        D: DrawTarget<Color = C>,
+    {
+        match self.background_color {
+            Some(color) => target.fill_solid(&area, color),
+            None => Ok(()),
+        }
+    }
+}
+
+impl<C> CharacterStyle for ProportionalTextStyle<'_, C>
+where
+    C: PixelColor,
+{
+    type Color = C;
+
+    fn set_text_color(&mut self, text_color: Option<C>) {
+        self.text_color = text_color;
+    }
+
+    fn set_background_color(&mut self, background_color: Option<C>) {
+        self.background_color = background_color;
+    }
+}
+
+impl<C> TextRenderer for ProportionalTextStyle<'_, C>
+where
+    C: PixelColor,
+{
+    type Color = C;
+
+    fn draw_string<D>(
+        &self,
+        text: &str,
+        position: Point,
+        baseline: Baseline,
+        target: &mut D,
+    ) -> Result<Point, D::Error>
+    where
+        D: DrawTarget<Color = C>,
+    {
+        let top_left = position - Point::new(0, self.baseline_offset(baseline));
+        let mut end = 0;
+
+        for placement in self.placements(text) {
+            self.fill_background(
+                Rectangle::new(
+                    top_left + Point::new(placement.start, 0),
+                    Size::new(placement.advance, self.font.height),
+                ),
+                target,
+            )?;
+
+            if let (Some(glyph), Some(color)) = (placement.glyph, self.text_color) {
+                let origin = top_left + Point::new(placement.x, 0);
+                let image = &self.font.image;
+                let pixels = (0..self.font.height as i32)
+                    .flat_map(|y| (0..glyph.width as i32).map(move |x| Point::new(x, y)))
+                    .filter(|point| {
+                        image.pixel(Point::new(glyph.x as i32 + point.x, point.y))
+                            == Some(BinaryColor::On)
+                    })
+                    .map(|point| Pixel(origin + point, color));
+                target.draw_iter(pixels)?;
+            }
+
+            end = placement.start + placement.advance as i32;
+        }
+
+        Ok(position + Point::new(end, 0))
+    }
+
+    fn draw_whitespace<D>(
+        &self,
+        width: u32,
+        position: Point,
+        baseline: Baseline,
+        target: &mut D,
+    ) -> Result<Point, D::Error>
+    where
+        D: DrawTarget<Color = C>,
+    {
+        let top_left = position - Point::new(0, self.baseline_offset(baseline));
+        self.fill_background(
+            Rectangle::new(top_left, Size::new(width, self.font.height)),
+            target,
+        )?;
+
+        Ok(position + Point::new(width as i32, 0))
+    }
+
+    fn measure_string(&self, text: &str, position: Point, baseline: Baseline) -> TextMetrics {
+        let mut width = 0;
+        let mut end = 0;
+        for placement in self.placements(text) {
+            // Up to the last lit column, leaving out the spacing after it
+            let right = match placement.glyph {
+                Some(glyph) => placement.x + glyph.width as i32,
+                None => placement.start + placement.advance as i32 - 1,
+            };
+            width = width.max(right);
+            end = placement.start + placement.advance as i32;
+        }
+
+        TextMetrics {
+            bounding_box: Rectangle::new(
+                position - Point::new(0, self.baseline_offset(baseline)),
+                Size::new(width.max(0) as u32, self.font.height),
+            ),
+            next_position: position + Point::new(end, 0),
+        }
+    }
+
+    fn line_height(&self) -> u32 {
+        self.font.height
+    }
+}
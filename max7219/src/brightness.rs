@@ -0,0 +1,18 @@
+/// Brightness, in percent, where each of the chip's 16 PWM steps takes over from the one below.
+///
+/// From step 4 up the thresholds are `100 * ((step - 0.5) / 15) ^ (1 / 1.7)` to within a
+/// percent, a gamma 1.7 curve through the middle of each step, so equal percentages look
+/// roughly like equal changes in brightness. The first three sit below that curve, so the
+/// dim end isn't one wide step. 0% is the dimmest step and 100% the brightest.
+const STEP_THRESHOLDS: [u8; 15] = [9, 23, 33, 42, 49, 56, 62, 67, 72, 77, 82, 86, 90, 94, 98];
+
+/// Maps a perceptual brightness of 0-100% onto the intensity register (0-15).
+///
+/// Even the lowest step lights the LEDs; use [`Driver::power_off`](crate::Driver::power_off)
+/// to turn them off.
+pub fn intensity_for(percent: u8) -> u8 {
+    STEP_THRESHOLDS
+        .iter()
+        .take_while(|threshold| percent >= **threshold)
+        .count() as u8
+}
@@ -0,0 +1,140 @@
+use crate::topology::{chain_address, unmount};
+use crate::{buffer_len, Corner, Driver, Grid, Orientation, Wiring, MAX_DIGITS};
+use embedded_hal::blocking::spi::Write;
+
+/// Physical layout of a grid of modules whose size is only known at runtime.
+///
+/// Works like [`Topology`](crate::Topology). Modules are all mounted the same way unless
+/// [`with_orientations`](Self::with_orientations) says otherwise.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct DynTopology<'a> {
+    rows: usize,
+    cols: usize,
+    start: Corner,
+    wiring: Wiring,
+    orientations: Orientations<'a>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Orientations<'a> {
+    Uniform(Orientation),
+    // One per panel position, row by row from the top left
+    PerModule(&'a [Orientation]),
+}
+
+impl<'a> DynTopology<'a> {
+    pub const fn new(rows: usize, cols: usize, start: Corner, wiring: Wiring) -> Self {
+        DynTopology {
+            rows,
+            cols,
+            start,
+            wiring,
+            orientations: Orientations::Uniform(Orientation {
+                rotation: crate::Rotation::None,
+                mirrored: false,
+            }),
+        }
+    }
+
+    /// Sets how every module on the panel is mounted.
+    pub fn with_orientation(mut self, orientation: Orientation) -> Self {
+        self.orientations = Orientations::Uniform(orientation);
+        self
+    }
+
+    /// Sets how each module is mounted, one orientation per panel position, row by row from
+    /// the top left.
+    ///
+    /// Panics unless there is exactly one orientation per module.
+    pub fn with_orientations(mut self, orientations: &'a [Orientation]) -> Self {
+        assert_eq!(
+            orientations.len(),
+            self.devices(),
+            "a {}x{} panel needs an orientation per module",
+            self.rows,
+            self.cols,
+        );
+        self.orientations = Orientations::PerModule(orientations);
+        self
+    }
+
+    pub fn rows(&self) -> usize {
+        self.rows
+    }
+
+    pub fn cols(&self) -> usize {
+        self.cols
+    }
+
+    pub fn devices(&self) -> usize {
+        self.rows * self.cols
+    }
+
+    /// Number of 8 byte blocks a [`DynMax7219`] driving this grid needs for its buffer.
+    pub fn buffer_len(&self) -> usize {
+        buffer_len(self.devices())
+    }
+
+    /// See [`Topology::locate`](crate::Topology::locate).
+    pub fn locate(&self, x: usize, y: usize) -> (usize, usize, usize) {
+        let (row, col) = (y / 8, x / 8);
+        let addr = chain_address(self.rows, self.cols, self.start, self.wiring, row, col);
+        let orientation = match self.orientations {
+            Orientations::Uniform(orientation) => orientation,
+            Orientations::PerModule(orientations) => orientations[row * self.cols + col],
+        };
+        let (column, digit) = unmount(orientation, x % 8, y % 8);
+
+        (addr, column, digit)
+    }
+}
+
+impl Grid for DynTopology<'_> {
+    fn rows(&self) -> usize {
+        self.rows
+    }
+
+    fn cols(&self) -> usize {
+        self.cols
+    }
+
+    fn locate(&self, x: usize, y: usize) -> (usize, usize, usize) {
+        DynTopology::locate(self, x, y)
+    }
+}
+
+/// Driver for a grid sized at runtime, so one build can drive any panel its configuration
+/// describes.
+///
+/// All state lives in a buffer the caller provides: a `&mut [[u8; 8]]` or a
+/// `Vec<[u8; 8]>` of at least [`DynTopology::buffer_len`] blocks. Otherwise it works
+/// exactly like a [`Max7219`](crate::Max7219).
+pub type DynMax7219<'a, SPI, B> = Driver<SPI, DynTopology<'a>, B>;
+
+impl<'a, SPI, B> DynMax7219<'a, SPI, B>
+where
+    SPI: Write<u8>,
+    B: AsMut<[[u8; MAX_DIGITS]]>,
+{
+    /// Panics if `buffer` is shorter than [`DynTopology::buffer_len`].
+    pub fn new(spi: SPI, topology: DynTopology<'a>, mut buffer: B) -> Self {
+        let len = buffer.as_mut().len();
+        assert!(
+            len >= topology.buffer_len(),
+            "a {}x{} panel needs a buffer of {} blocks, got {len}",
+            topology.rows,
+            topology.cols,
+            topology.buffer_len(),
+        );
+        buffer.as_mut().fill([0; MAX_DIGITS]);
+
+        Driver {
+            spi,
+            topology,
+            buffer,
+            stale: true,
+            intensity: 1,
+            subframe: 0,
+        }
+    }
+}
@@ -0,0 +1,217 @@
+use crate::{DynTopology, Grid, Topology, MAX_DIGITS};
+use embedded_graphics_core::{
+    pixelcolor::BinaryColor,
+    prelude::{Dimensions, DrawTarget, OriginDimensions, Point, PointsIter, Size},
+    primitives::Rectangle,
+    Pixel,
+};
+
+/// A frame of on/off pixels that [`Driver::present_frame`](crate::Driver::present_frame)
+/// can show.
+pub trait BinaryFrame {
+    /// The frame's digits, one display per chain address.
+    fn digits_mut(&mut self) -> &mut [[u8; MAX_DIGITS]];
+}
+
+/// One full frame for a `ROWS` x `COLS` grid, kept in the order the chain expects it.
+///
+/// Frames are plain data, so they can be drawn on another thread and handed to
+/// [`Driver::present_frame`](crate::Driver::present_frame) once complete.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub struct FrameBuffer<const ROWS: usize, const COLS: usize> {
+    topology: Topology<ROWS, COLS>,
+    // Indexed like the chain: device `n` lives at `buffer[n / COLS][n % COLS]`
+    buffer: [[[u8; MAX_DIGITS]; COLS]; ROWS],
+}
+
+impl<const ROWS: usize, const COLS: usize> FrameBuffer<ROWS, COLS> {
+    /// A blank frame for a panel wired as `topology`.
+    pub const fn new(topology: Topology<ROWS, COLS>) -> Self {
+        FrameBuffer {
+            topology,
+            buffer: [[[0; MAX_DIGITS]; COLS]; ROWS],
+        }
+    }
+
+    pub fn topology(&self) -> Topology<ROWS, COLS> {
+        self.topology
+    }
+
+    pub(crate) fn rows_mut(&mut self) -> &mut [[[u8; MAX_DIGITS]; COLS]; ROWS] {
+        &mut self.buffer
+    }
+
+    /// Color of the pixel at `point`, `Off` outside the frame.
+    pub fn pixel(&self, point: Point) -> BinaryColor {
+        if !self.bounding_box().contains(point) {
+            return BinaryColor::Off;
+        }
+
+        pixel(self.buffer.as_flattened(), &self.topology, point)
+    }
+
+    /// Sets the pixel at `point`, which must be inside the frame.
+    fn set_pixel(&mut self, point: Point, color: BinaryColor) {
+        set_pixel(self.buffer.as_flattened_mut(), &self.topology, point, color);
+    }
+}
+
+/// Color of the pixel at `point` in `digits`, which hold one display per chain address of
+/// `grid`. The point must be inside the grid.
+pub(crate) fn pixel(digits: &[[u8; MAX_DIGITS]], grid: &impl Grid, point: Point) -> BinaryColor {
+    let (display, column, digit) = grid.locate(point.x as usize, point.y as usize);
+    (digits[display][digit] & 1 << (7 - column) != 0).into()
+}
+
+/// Sets the pixel at `point` in `digits`, see [`pixel`].
+pub(crate) fn set_pixel(
+    digits: &mut [[u8; MAX_DIGITS]],
+    grid: &impl Grid,
+    point: Point,
+    color: BinaryColor,
+) {
+    let (display, column, digit) = grid.locate(point.x as usize, point.y as usize);
+    let digits = &mut digits[display];
+    match color {
+        BinaryColor::On => digits[digit] |= 1 << (7 - column),
+        BinaryColor::Off => digits[digit] &= !(1 << (7 - column)),
+    }
+}
+
+impl<const ROWS: usize, const COLS: usize> BinaryFrame for FrameBuffer<ROWS, COLS> {
+    fn digits_mut(&mut self) -> &mut [[u8; MAX_DIGITS]] {
+        self.buffer.as_flattened_mut()
+    }
+}
+
+impl<const ROWS: usize, const COLS: usize> DrawTarget for FrameBuffer<ROWS, COLS> {
+    type Color = BinaryColor;
+    type Error = core::convert::Infallible;
+
+    fn draw_iter<I>(&mut self, pixels: I) -> Result<(), Self::Error>
+    where
+        I: IntoIterator<Item = Pixel<BinaryColor>>,
+    {
+        let bb = self.bounding_box();
+
+        pixels
+            .into_iter()
+            .filter(|Pixel(pos, _color)| bb.contains(*pos))
+            .for_each(|Pixel(pos, color)| self.set_pixel(pos, color));
+
+        Ok(())
+    }
+
+    fn fill_contiguous<I>(&mut self, area: &Rectangle, colors: I) -> Result<(), Self::Error>
+    where
+        I: IntoIterator<Item = Self::Color>,
+    {
+        self.draw_iter(
+            area.points()
+                .zip(colors)
+                .map(|(pos, color)| Pixel(pos, color)),
+        )
+    }
+
+    fn fill_solid(&mut self, area: &Rectangle, color: Self::Color) -> Result<(), Self::Error> {
+        self.fill_contiguous(area, core::iter::repeat(color))
+    }
+
+    fn clear(&mut self, color: Self::Color) -> Result<(), Self::Error> {
+        self.fill_solid(&self.bounding_box(), color)
+    }
+}
+
+impl<const ROWS: usize, const COLS: usize> OriginDimensions for FrameBuffer<ROWS, COLS> {
+    fn size(&self) -> Size {
+        // The width is `cols * 8` and the height is `rows * 8`
+        Size::new((COLS * 8) as u32, (ROWS * 8) as u32)
+    }
+}
+
+/// A [`FrameBuffer`] for a grid sized at runtime.
+///
+/// The digits live in a buffer the caller provides: a `&mut [[u8; 8]]` or a `Vec<[u8; 8]>`
+/// of at least [`DynTopology::devices`] blocks.
+pub struct DynFrameBuffer<'a, B> {
+    topology: DynTopology<'a>,
+    // One block per chain address
+    digits: B,
+}
+
+impl<'a, B> DynFrameBuffer<'a, B>
+where
+    B: AsRef<[[u8; MAX_DIGITS]]> + AsMut<[[u8; MAX_DIGITS]]>,
+{
+    /// A blank frame for a panel wired as `topology`.
+    ///
+    /// Panics if `digits` is shorter than [`DynTopology::devices`].
+    pub fn new(topology: DynTopology<'a>, mut digits: B) -> Self {
+        let len = digits.as_mut().len();
+        assert!(
+            len >= topology.devices(),
+            "a {}x{} panel needs {} blocks of digits, got {len}",
+            topology.rows(),
+            topology.cols(),
+            topology.devices(),
+        );
+        digits.as_mut().fill([0; MAX_DIGITS]);
+
+        DynFrameBuffer { topology, digits }
+    }
+
+    pub fn topology(&self) -> DynTopology<'a> {
+        self.topology
+    }
+
+    /// Color of the pixel at `point`, `Off` outside the frame.
+    pub fn pixel(&self, point: Point) -> BinaryColor {
+        if !self.bounding_box().contains(point) {
+            return BinaryColor::Off;
+        }
+
+        pixel(self.digits.as_ref(), &self.topology, point)
+    }
+}
+
+impl<B> BinaryFrame for DynFrameBuffer<'_, B>
+where
+    B: AsMut<[[u8; MAX_DIGITS]]>,
+{
+    fn digits_mut(&mut self) -> &mut [[u8; MAX_DIGITS]] {
+        &mut self.digits.as_mut()[..self.topology.devices()]
+    }
+}
+
+impl<B> DrawTarget for DynFrameBuffer<'_, B>
+where
+    B: AsMut<[[u8; MAX_DIGITS]]>,
+{
+    type Color = BinaryColor;
+    type Error = core::convert::Infallible;
+
+    fn draw_iter<I>(&mut self, pixels: I) -> Result<(), Self::Error>
+    where
+        I: IntoIterator<Item = Pixel<BinaryColor>>,
+    {
+        let bb = self.bounding_box();
+        let digits = self.digits.as_mut();
+
+        pixels
+            .into_iter()
+            .filter(|Pixel(pos, _color)| bb.contains(*pos))
+            .for_each(|Pixel(pos, color)| set_pixel(digits, &self.topology, pos, color));
+
+        Ok(())
+    }
+}
+
+impl<B> OriginDimensions for DynFrameBuffer<'_, B> {
+    fn size(&self) -> Size {
+        // The width is `cols * 8` and the height is `rows * 8`
+        Size::new(
+            (self.topology.cols() * 8) as u32,
+            (self.topology.rows() * 8) as u32,
+        )
+    }
+}
@@ -0,0 +1,237 @@
+use crate::{DynTopology, FrameBuffer, Topology, MAX_DIGITS};
+use core::marker::PhantomData;
+use embedded_graphics_core::{
+    pixelcolor::{raw::RawData, GrayColor, PixelColor},
+    prelude::{Dimensions, DrawTarget, OriginDimensions, Size},
+    Pixel,
+};
+
+/// Luma of every pixel of a module, indexed by `(digit, column)`.
+type Levels = [[u8; 8]; MAX_DIGITS];
+
+/// A frame in shades of gray that [`Driver::present_gray`](crate::Driver::present_gray)
+/// can show.
+pub trait GrayFrame {
+    /// How many subframes make up one cycle: 3 for `Gray2`, 15 for `Gray4`.
+    fn subframes(&self) -> u8;
+
+    /// Renders subframe `subframe` of the cycle into `digits`, one display per chain
+    /// address.
+    fn render_digits(&self, subframe: u8, digits: &mut [[u8; MAX_DIGITS]]);
+}
+
+/// A frame in shades of gray, such as `Gray2` or `Gray4`, for a `ROWS` x `COLS` grid.
+///
+/// The chips can only switch LEDs on and off, so the frame is shown as a cycle of binary
+/// subframes, one per step of `C` above black, with each pixel lit in as many of them as
+/// its luma. Hand it to [`Driver::present_gray`](crate::Driver::present_gray) fast
+/// enough and the eye averages the cycle out.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub struct GrayFrameBuffer<C, const ROWS: usize, const COLS: usize> {
+    topology: Topology<ROWS, COLS>,
+    // Indexed like the chain: device `n` lives at `levels[n / COLS][n % COLS]`
+    levels: [[Levels; COLS]; ROWS],
+    color: PhantomData<C>,
+}
+
+impl<C, const ROWS: usize, const COLS: usize> GrayFrameBuffer<C, ROWS, COLS>
+where
+    C: GrayColor,
+{
+    /// A black frame for a panel wired as `topology`.
+    pub const fn new(topology: Topology<ROWS, COLS>) -> Self {
+        GrayFrameBuffer {
+            topology,
+            levels: [[[[0; 8]; MAX_DIGITS]; COLS]; ROWS],
+            color: PhantomData,
+        }
+    }
+
+    pub fn topology(&self) -> Topology<ROWS, COLS> {
+        self.topology
+    }
+
+    /// How many subframes make up one cycle: 3 for `Gray2`, 15 for `Gray4`.
+    pub fn subframes(&self) -> u8 {
+        subframes::<C>()
+    }
+
+    /// Renders subframe `subframe` of the cycle into `frame`.
+    ///
+    /// A pixel of luma `n` is lit in `n` of the subframes, spread as evenly over the cycle
+    /// as they go, so dim pixels flicker as little as possible.
+    pub fn render_subframe(&self, subframe: u8, frame: &mut FrameBuffer<ROWS, COLS>) {
+        self.render_digits(subframe, frame.rows_mut().as_flattened_mut());
+    }
+}
+
+impl<C, const ROWS: usize, const COLS: usize> GrayFrame for GrayFrameBuffer<C, ROWS, COLS>
+where
+    C: GrayColor,
+{
+    fn subframes(&self) -> u8 {
+        subframes::<C>()
+    }
+
+    fn render_digits(&self, subframe: u8, digits: &mut [[u8; MAX_DIGITS]]) {
+        render(
+            self.levels.as_flattened(),
+            subframes::<C>(),
+            subframe,
+            digits,
+        );
+    }
+}
+
+impl<C, const ROWS: usize, const COLS: usize> DrawTarget for GrayFrameBuffer<C, ROWS, COLS>
+where
+    C: GrayColor,
+{
+    type Color = C;
+    type Error = core::convert::Infallible;
+
+    fn draw_iter<I>(&mut self, pixels: I) -> Result<(), Self::Error>
+    where
+        I: IntoIterator<Item = Pixel<C>>,
+    {
+        let bb = self.bounding_box();
+
+        pixels
+            .into_iter()
+            .filter(|Pixel(pos, _color)| bb.contains(*pos))
+            .for_each(|Pixel(pos, color)| {
+                let (display, column, digit) = self.topology.locate(pos.x as usize, pos.y as usize);
+                self.levels[display / COLS][display % COLS][digit][column] = color.luma();
+            });
+
+        Ok(())
+    }
+}
+
+impl<C, const ROWS: usize, const COLS: usize> OriginDimensions for GrayFrameBuffer<C, ROWS, COLS>
+where
+    C: PixelColor,
+{
+    fn size(&self) -> Size {
+        // The width is `cols * 8` and the height is `rows * 8`
+        Size::new((COLS * 8) as u32, (ROWS * 8) as u32)
+    }
+}
+
+/// A [`GrayFrameBuffer`] for a grid sized at runtime.
+///
+/// The levels live in a buffer the caller provides: a `&mut [[[u8; 8]; 8]]` or a
+/// `Vec<[[u8; 8]; 8]>` of at least [`DynTopology::devices`] blocks.
+pub struct DynGrayFrameBuffer<'a, C, B> {
+    topology: DynTopology<'a>,
+    // One block per chain address
+    levels: B,
+    color: PhantomData<C>,
+}
+
+impl<'a, C, B> DynGrayFrameBuffer<'a, C, B>
+where
+    C: GrayColor,
+    B: AsRef<[Levels]> + AsMut<[Levels]>,
+{
+    /// A black frame for a panel wired as `topology`.
+    ///
+    /// Panics if `levels` is shorter than [`DynTopology::devices`].
+    pub fn new(topology: DynTopology<'a>, mut levels: B) -> Self {
+        let len = levels.as_mut().len();
+        assert!(
+            len >= topology.devices(),
+            "a {}x{} panel needs {} blocks of levels, got {len}",
+            topology.rows(),
+            topology.cols(),
+            topology.devices(),
+        );
+        levels.as_mut().fill([[0; 8]; MAX_DIGITS]);
+
+        DynGrayFrameBuffer {
+            topology,
+            levels,
+            color: PhantomData,
+        }
+    }
+
+    pub fn topology(&self) -> DynTopology<'a> {
+        self.topology
+    }
+}
+
+impl<C, B> GrayFrame for DynGrayFrameBuffer<'_, C, B>
+where
+    C: GrayColor,
+    B: AsRef<[Levels]>,
+{
+    fn subframes(&self) -> u8 {
+        subframes::<C>()
+    }
+
+    fn render_digits(&self, subframe: u8, digits: &mut [[u8; MAX_DIGITS]]) {
+        render(self.levels.as_ref(), subframes::<C>(), subframe, digits);
+    }
+}
+
+impl<C, B> DrawTarget for DynGrayFrameBuffer<'_, C, B>
+where
+    C: GrayColor,
+    B: AsMut<[Levels]>,
+{
+    type Color = C;
+    type Error = core::convert::Infallible;
+
+    fn draw_iter<I>(&mut self, pixels: I) -> Result<(), Self::Error>
+    where
+        I: IntoIterator<Item = Pixel<C>>,
+    {
+        let bb = self.bounding_box();
+        let levels = self.levels.as_mut();
+
+        pixels
+            .into_iter()
+            .filter(|Pixel(pos, _color)| bb.contains(*pos))
+            .for_each(|Pixel(pos, color)| {
+                let (display, column, digit) = self.topology.locate(pos.x as usize, pos.y as usize);
+                levels[display][digit][column] = color.luma();
+            });
+
+        Ok(())
+    }
+}
+
+impl<C, B> OriginDimensions for DynGrayFrameBuffer<'_, C, B> {
+    fn size(&self) -> Size {
+        // The width is `cols * 8` and the height is `rows * 8`
+        Size::new(
+            (self.topology.cols() * 8) as u32,
+            (self.topology.rows() * 8) as u32,
+        )
+    }
+}
+
+fn subframes<C: GrayColor>() -> u8 {
+    ((1u16 << C::Raw::BITS_PER_PIXEL) - 1) as u8
+}
+
+/// Renders subframe `subframe` of a cycle of `subframes` from `levels` into `digits`, both
+/// one block per chain address.
+///
+/// A pixel of luma `n` is lit in `n` of the subframes, spread as evenly over the cycle as
+/// they go, so dim pixels flicker as little as possible.
+fn render(levels: &[Levels], subframes: u8, subframe: u8, digits: &mut [[u8; MAX_DIGITS]]) {
+    let subframes = subframes as u16;
+    let subframe = (subframe as u16) % subframes;
+
+    for (levels, digits) in levels.iter().zip(digits.iter_mut()) {
+        for (columns, digit) in levels.iter().zip(digits.iter_mut()) {
+            *digit = columns.iter().fold(0, |bits, level| {
+                // Lit whenever the running total of `level / subframes` ticks over
+                let level = *level as u16;
+                let lit = (subframe + 1) * level / subframes != subframe * level / subframes;
+                bits << 1 | lit as u8
+            });
+        }
+    }
+}
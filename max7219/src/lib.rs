@@ -0,0 +1,657 @@
+//! Driver for chains of MAX7219 8x8 LED matrix modules laid out as a grid, exposed as an
+//! `embedded-graphics` [`DrawTarget`]. The grid is sized at compile time with [`Max7219`]
+//! or at runtime with [`DynMax7219`], which share one implementation in [`Driver`].
+#![no_std]
+
+use embedded_graphics_core::{
+    pixelcolor::BinaryColor,
+    prelude::{Dimensions, DrawTarget, OriginDimensions, PointsIter, Size},
+    primitives::Rectangle,
+    Pixel,
+};
+use embedded_hal::blocking::spi::Write;
+
+mod brightness;
+mod dynamic;
+mod frame;
+mod gray;
+mod segments;
+mod topology;
+mod transition;
+
+pub use brightness::intensity_for;
+pub use dynamic::{DynMax7219, DynTopology};
+pub use frame::{BinaryFrame, DynFrameBuffer, FrameBuffer};
+pub use gray::{DynGrayFrameBuffer, GrayFrame, GrayFrameBuffer};
+use segments::CODE_B_BLANK;
+pub use segments::{code_b_for, segments_for, DECIMAL_POINT};
+pub use topology::{Corner, Grid, Orientation, Rotation, Topology, UnknownName, Wiring};
+pub use transition::{Sample, Transition};
+
+const MAX_DIGITS: usize = 8;
+const MAX_INTENSITY: u8 = 0x0F;
+
+#[derive(Clone, Copy)]
+pub enum Command {
+    NoOp = 0x00,
+    DecodeMode = 0x09,
+    Intensity = 0x0A,
+    ScanLimit = 0x0B,
+    Power = 0x0C,
+    DisplayTest = 0x0F,
+}
+
+/// Decode modes for BCD encoded input.
+///
+/// Digits with Code B decoding take a [`code_b_for`] value, the others take raw segments.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum DecodeMode {
+    #[default]
+    NoDecode = 0x00,
+    /// Code B on digit 0 only.
+    CodeBDigit0 = 0x01,
+    /// Code B on digits 0-3, raw segments on digits 4-7.
+    CodeBDigits0To3 = 0x0F,
+    /// Code B on every digit.
+    CodeBAll = 0xFF,
+}
+
+impl DecodeMode {
+    /// Whether `digit` (0-7) is Code B decoded.
+    pub fn decodes(self, digit: usize) -> bool {
+        (self as u8) >> digit & 1 == 1
+    }
+}
+
+/// Kind of module at a position in the chain.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Module {
+    /// 8x8 LED matrix, showing its part of the frame.
+    #[default]
+    Matrix,
+    /// Eight 7-segment digits, showing what's written with [`Driver::write_str`] or
+    /// [`Driver::set_digit`].
+    SevenSegment(DecodeMode),
+}
+
+impl Module {
+    fn decode_mode(self) -> DecodeMode {
+        match self {
+            Module::Matrix => DecodeMode::NoDecode,
+            Module::SevenSegment(mode) => mode,
+        }
+    }
+}
+
+/// Driver for a chain of MAX7219 modules laid out as a [`Grid`], keeping the frames and
+/// the state of every module in `buffer`.
+///
+/// Use it as a [`Max7219`], which sizes the grid and buffer at compile time, or as a
+/// [`DynMax7219`], which takes both at runtime.
+pub struct Driver<SPI, T, B>
+where
+    SPI: Write<u8>,
+    T: Grid,
+    B: Storage,
+{
+    spi: SPI,
+    topology: T,
+    // Frames, digits and settings of every module, see `split`
+    buffer: B,
+    // Set while the shown digits can't be trusted, so the next flush rewrites every digit
+    stale: bool,
+    // Intensity step for the whole panel, which each module's trim is an offset from
+    intensity: u8,
+    // Subframe of the gray cycle shown last
+    subframe: u8,
+}
+
+/// Driver for a `ROWS` x `COLS` grid, with its buffer sized at compile time.
+pub type Max7219<SPI, const ROWS: usize, const COLS: usize> =
+    Driver<SPI, Topology<ROWS, COLS>, FixedBuffer<ROWS, COLS>>;
+
+/// Where a [`Driver`] keeps its state: six blocks per module, and room for the SPI frame.
+///
+/// Implemented by the [`FixedBuffer`] of a [`Max7219`] and by any `&mut [[u8; 8]]` or
+/// `Vec<[u8; 8]>` of at least [`DynTopology::buffer_len`] blocks for a [`DynMax7219`].
+pub trait Storage {
+    /// The blocks and the SPI frame bytes of a chain of `devices` modules.
+    fn split(&mut self, devices: usize) -> (&mut [[u8; MAX_DIGITS]], &mut [u8]);
+}
+
+impl<B: AsMut<[[u8; MAX_DIGITS]]>> Storage for B {
+    fn split(&mut self, devices: usize) -> (&mut [[u8; MAX_DIGITS]], &mut [u8]) {
+        let (blocks, frame) = self.as_mut().split_at_mut(PARTS * devices);
+        (blocks, &mut frame.as_flattened_mut()[..devices * 2])
+    }
+}
+
+/// Buffer of a [`Max7219`]: every part of the driver's state, and the SPI frame.
+pub struct FixedBuffer<const ROWS: usize, const COLS: usize> {
+    blocks: [[[[u8; MAX_DIGITS]; COLS]; ROWS]; PARTS],
+    frame: [[[u8; 2]; COLS]; ROWS],
+}
+
+impl<const ROWS: usize, const COLS: usize> Storage for FixedBuffer<ROWS, COLS> {
+    fn split(&mut self, _devices: usize) -> (&mut [[u8; MAX_DIGITS]], &mut [u8]) {
+        (
+            self.blocks.as_flattened_mut().as_flattened_mut(),
+            self.frame.as_flattened_mut().as_flattened_mut(),
+        )
+    }
+}
+
+/// Number of blocks every module takes up in a driver buffer, one for each of `Parts`
+/// except the SPI frame.
+const PARTS: usize = 6;
+
+/// Number of 8 byte blocks the state of a chain of `devices` modules takes up.
+pub(crate) const fn buffer_len(devices: usize) -> usize {
+    // Plus a [register, data] pair per device
+    PARTS * devices + devices.div_ceil(4)
+}
+
+/// A driver buffer, split up. Every part but `frame` holds one block per chain address.
+struct Parts<'a> {
+    // The frame on the panel, and the one being drawn
+    front: &'a mut [[u8; MAX_DIGITS]],
+    back: &'a mut [[u8; MAX_DIGITS]],
+    // What goes out on the next send
+    scratch: &'a mut [[u8; MAX_DIGITS]],
+    // What the chips are currently showing
+    shown: &'a mut [[u8; MAX_DIGITS]],
+    // The digits of 7-segment modules
+    segments: &'a mut [[u8; MAX_DIGITS]],
+    // Kind, decode mode and trim of every module, see `Settings`
+    settings: &'a mut [[u8; MAX_DIGITS]],
+    // One [register, data] pair per device
+    frame: &'a mut [u8],
+}
+
+fn split(buffer: &mut impl Storage, devices: usize) -> Parts<'_> {
+    let (blocks, frame) = buffer.split(devices);
+    let (front, rest) = blocks.split_at_mut(devices);
+    let (back, rest) = rest.split_at_mut(devices);
+    let (scratch, rest) = rest.split_at_mut(devices);
+    let (shown, rest) = rest.split_at_mut(devices);
+    let (segments, rest) = rest.split_at_mut(devices);
+    let settings = rest;
+
+    Parts {
+        front,
+        back,
+        scratch,
+        shown,
+        segments,
+        settings,
+        frame,
+    }
+}
+
+/// Positions in a module's settings block. An all zero block is a matrix with no trim.
+struct Settings;
+
+impl Settings {
+    const SEVEN_SEGMENT: usize = 0;
+    const DECODE_MODE: usize = 1;
+    const TRIM: usize = 2;
+
+    fn module(block: &[u8; MAX_DIGITS]) -> Module {
+        if block[Self::SEVEN_SEGMENT] == 0 {
+            return Module::Matrix;
+        }
+
+        Module::SevenSegment(match block[Self::DECODE_MODE] {
+            0x01 => DecodeMode::CodeBDigit0,
+            0x0F => DecodeMode::CodeBDigits0To3,
+            0xFF => DecodeMode::CodeBAll,
+            _ => DecodeMode::NoDecode,
+        })
+    }
+
+    fn set_module(block: &mut [u8; MAX_DIGITS], module: Module) {
+        block[Self::SEVEN_SEGMENT] = (module != Module::Matrix) as u8;
+        block[Self::DECODE_MODE] = module.decode_mode() as u8;
+    }
+
+    /// `intensity` offset by the module's trim.
+    fn trimmed(block: &[u8; MAX_DIGITS], intensity: u8) -> u8 {
+        let trim = block[Self::TRIM] as i8;
+        (intensity as i16 + trim as i16).clamp(0, MAX_INTENSITY as i16) as u8
+    }
+}
+
+impl<SPI, const ROWS: usize, const COLS: usize> Max7219<SPI, ROWS, COLS>
+where
+    SPI: Write<u8>,
+{
+    pub fn new(spi: SPI, topology: Topology<ROWS, COLS>) -> Self {
+        Driver {
+            spi,
+            topology,
+            buffer: FixedBuffer {
+                blocks: [[[[0; MAX_DIGITS]; COLS]; ROWS]; PARTS],
+                frame: [[[0; 2]; COLS]; ROWS],
+            },
+            stale: true,
+            intensity: 1,
+            subframe: 0,
+        }
+    }
+
+    /// A blank frame wired like this panel, to draw on elsewhere and hand to
+    /// [`present_frame`](Driver::present_frame).
+    pub fn new_frame(&self) -> FrameBuffer<ROWS, COLS> {
+        FrameBuffer::new(self.topology)
+    }
+}
+
+impl<SPI, T, B> Driver<SPI, T, B>
+where
+    SPI: Write<u8>,
+    T: Grid,
+    B: Storage,
+{
+    pub fn topology(&self) -> T {
+        self.topology
+    }
+
+    fn parts(&mut self) -> Parts<'_> {
+        split(&mut self.buffer, self.topology.devices())
+    }
+
+    /// Sets up every device and blanks it, leaving the panel shut down.
+    ///
+    /// Every step goes to the whole chain in a single transaction.
+    pub fn init(&mut self) -> Result<(), SPI::Error> {
+        self.write_all(Command::DisplayTest as u8, 0x00)?; // turn testmode off
+        self.write_all(Command::ScanLimit as u8, 0x07)?; // set scanlimit
+        self.write_each(Command::DecodeMode, |settings| {
+            Settings::module(settings).decode_mode() as u8
+        })?; // decode as configured
+        for digit in 1..=MAX_DIGITS as u8 {
+            self.write_all(digit, 0x00)?; // clear all digits
+        }
+        self.parts().shown.fill([0; MAX_DIGITS]);
+        self.stale = false;
+        self.set_global_intensity(self.intensity)?;
+        self.power_off()
+    }
+
+    pub fn test(&mut self, addr: usize, is_on: bool) -> Result<(), SPI::Error> {
+        if is_on {
+            self.write_data(addr, Command::DisplayTest, 0x01)
+        } else {
+            self.write_data(addr, Command::DisplayTest, 0x00)
+        }
+    }
+
+    pub fn set_decode_mode(&mut self, addr: usize, mode: DecodeMode) -> Result<(), SPI::Error> {
+        self.write_data(addr, Command::DecodeMode, mode as u8)
+    }
+
+    fn write_data(&mut self, addr: usize, command: Command, data: u8) -> Result<(), SPI::Error> {
+        self.write_raw(addr, command as u8, data)
+    }
+
+    /// Writes `data` to `header` on the device at `addr`, and a no-op to all the others.
+    fn write_raw(&mut self, addr: usize, header: u8, data: u8) -> Result<(), SPI::Error> {
+        let Parts { frame, .. } = split(&mut self.buffer, self.topology.devices());
+        frame.fill(Command::NoOp as u8);
+        frame[addr * 2..addr * 2 + 2].copy_from_slice(&[header, data]);
+
+        self.spi.write(frame)
+    }
+
+    /// Writes `data` to `register` on every device in a single transaction.
+    fn write_all(&mut self, register: u8, data: u8) -> Result<(), SPI::Error> {
+        let Parts { frame, .. } = split(&mut self.buffer, self.topology.devices());
+        for pair in frame.chunks_exact_mut(2) {
+            pair.copy_from_slice(&[register, data]);
+        }
+
+        self.spi.write(frame)
+    }
+
+    /// Writes `command` to every device in a single transaction, with the data `data` works
+    /// out from each one's settings.
+    fn write_each(
+        &mut self,
+        command: Command,
+        data: impl Fn(&[u8; MAX_DIGITS]) -> u8,
+    ) -> Result<(), SPI::Error> {
+        let Parts {
+            settings, frame, ..
+        } = split(&mut self.buffer, self.topology.devices());
+        for (pair, settings) in frame.chunks_exact_mut(2).zip(settings.iter()) {
+            pair.copy_from_slice(&[command as u8, data(settings)]);
+        }
+
+        self.spi.write(frame)
+    }
+
+    pub fn power_on(&mut self) -> Result<(), SPI::Error> {
+        self.write_all(Command::Power as u8, 0x01)
+    }
+
+    pub fn power_off(&mut self) -> Result<(), SPI::Error> {
+        self.write_all(Command::Power as u8, 0x00)
+    }
+
+    pub fn clear_display(&mut self, addr: usize) -> Result<(), SPI::Error> {
+        for i in 1..9 {
+            self.write_raw(addr, i, 0x00)?;
+        }
+        self.parts().shown[addr] = [0; MAX_DIGITS];
+
+        Ok(())
+    }
+
+    pub fn set_intensity(&mut self, addr: usize, intensity: u8) -> Result<(), SPI::Error> {
+        self.write_data(addr, Command::Intensity, intensity)
+    }
+
+    /// Sets every module to `intensity` (0-15) plus its trim, in a single transaction.
+    pub fn set_global_intensity(&mut self, intensity: u8) -> Result<(), SPI::Error> {
+        let intensity = intensity.min(MAX_INTENSITY);
+        self.intensity = intensity;
+
+        self.write_each(Command::Intensity, |settings| {
+            Settings::trimmed(settings, intensity)
+        })
+    }
+
+    /// Sets every module on a perceptual 0-100% scale, see [`intensity_for`].
+    pub fn set_brightness(&mut self, percent: u8) -> Result<(), SPI::Error> {
+        self.set_global_intensity(intensity_for(percent))
+    }
+
+    /// Offsets the module at `addr` by `trim` steps from the global intensity, to even out
+    /// modules from brighter or dimmer LED batches.
+    pub fn set_trim(&mut self, addr: usize, trim: i8) -> Result<(), SPI::Error> {
+        let intensity = self.intensity;
+        let settings = &mut self.parts().settings[addr];
+        settings[Settings::TRIM] = trim as u8;
+        let trimmed = Settings::trimmed(settings, intensity);
+
+        self.set_intensity(addr, trimmed)
+    }
+
+    fn module(&mut self, addr: usize) -> Module {
+        Settings::module(&self.parts().settings[addr])
+    }
+
+    /// Declares the module at `addr` as `module` and sets its decode mode to match.
+    ///
+    /// Matrix modules show their part of the frame, 7-segment ones keep their own digits
+    /// and leave the frame's pixels over them unshown.
+    pub fn set_module(&mut self, addr: usize, module: Module) -> Result<(), SPI::Error> {
+        Settings::set_module(&mut self.parts().settings[addr], module);
+        self.write_str(addr, "");
+        self.set_decode_mode(addr, module.decode_mode())?;
+        self.invalidate();
+
+        Ok(())
+    }
+
+    /// Sets the register value of `digit` (0-7) on a 7-segment module, sent on the next
+    /// [`flush`](Self::flush): a [`code_b_for`] value on Code B digits, [`segments_for`]
+    /// segments on the others, plus [`DECIMAL_POINT`] either way.
+    pub fn set_digit(&mut self, addr: usize, digit: usize, value: u8) {
+        self.parts().segments[addr][digit] = value;
+    }
+
+    /// Writes `text` to a 7-segment module, sent on the next [`flush`](Self::flush).
+    ///
+    /// The text starts at digit 7, which is the leftmost on the common 8-digit boards, and
+    /// is blanked after its end. A `.` lights the decimal point of the character before it,
+    /// and characters a digit can't show come out blank.
+    pub fn write_str(&mut self, addr: usize, text: &str) {
+        let mode = self.module(addr).decode_mode();
+        let digits = &mut self.parts().segments[addr];
+        let mut chars = text.chars().peekable();
+
+        for digit in (0..MAX_DIGITS).rev() {
+            let value = match chars.next() {
+                // A leading or doubled point gets a digit of its own
+                Some('.') => Some((' ', DECIMAL_POINT)),
+                Some(c) if chars.peek() == Some(&'.') => {
+                    chars.next();
+                    Some((c, DECIMAL_POINT))
+                }
+                Some(c) => Some((c, 0)),
+                None => None,
+            };
+            let (c, point) = value.unwrap_or((' ', 0));
+
+            let value = if mode.decodes(digit) {
+                code_b_for(c).unwrap_or(CODE_B_BLANK)
+            } else {
+                segments_for(c)
+            };
+            digits[digit] = value | point;
+        }
+    }
+
+    /// Writes `raw` straight to the digit registers of the module at `addr`.
+    pub fn write_display(&mut self, addr: usize, raw: &[u8; MAX_DIGITS]) -> Result<(), SPI::Error> {
+        for (digit, b) in (1u8..).zip(raw.iter()) {
+            self.write_raw(addr, digit, *b)?;
+        }
+        self.parts().shown[addr] = *raw;
+
+        Ok(())
+    }
+
+    /// Puts the back buffer, where the `DrawTarget` impl draws, on the panel.
+    ///
+    /// The buffers are swapped, so the back buffer then holds the frame that was shown before.
+    pub fn present(&mut self) -> Result<(), SPI::Error> {
+        let Parts { front, back, .. } = self.parts();
+        front.swap_with_slice(back);
+        self.flush()
+    }
+
+    /// Puts `frame` on the panel, leaving the frame that was shown before in `frame`.
+    ///
+    /// Panics if `frame` is for a panel with a different number of modules.
+    pub fn present_frame<F: BinaryFrame>(&mut self, frame: &mut F) -> Result<(), SPI::Error> {
+        let front = self.parts().front;
+        let digits = frame.digits_mut();
+        assert_eq!(
+            digits.len(),
+            front.len(),
+            "the frame is for a panel of a different size"
+        );
+        front.swap_with_slice(digits);
+        self.flush()
+    }
+
+    /// Puts the next subframe of `frame` on the panel, see [`GrayFrameBuffer`].
+    ///
+    /// Call this at a steady, high rate, once per subframe, for as long as `frame` should be
+    /// shown; only the digits that differ from the previous subframe are sent. The subframe
+    /// replaces the front buffer, the back buffer is left alone.
+    pub fn present_gray<G: GrayFrame>(&mut self, frame: &G) -> Result<(), SPI::Error> {
+        self.subframe = self.subframe.wrapping_add(1) % frame.subframes();
+        let subframe = self.subframe;
+        frame.render_digits(subframe, self.parts().front);
+        self.flush()
+    }
+
+    /// Makes the next [`flush`](Self::flush) rewrite every digit, e.g. after the chips were
+    /// power cycled or written to behind the driver's back.
+    pub fn invalidate(&mut self) {
+        self.stale = true;
+    }
+
+    /// Sends the digits of the front buffer that changed since the last flush.
+    ///
+    /// Every frame carries at most one changed digit per display, so a frame is only sent
+    /// while some display still has changes left, and displays without any get a no-op.
+    pub fn flush(&mut self) -> Result<(), SPI::Error> {
+        let Parts { front, scratch, .. } = self.parts();
+        scratch.copy_from_slice(front);
+        self.send()
+    }
+
+    /// Shows a frame `progress` of the way through `transition` from the front buffer, on
+    /// the panel, to the back buffer, within `area`. Outside `area` the back buffer shows.
+    ///
+    /// Neither buffer changes, so call this with a rising `progress` for every step of the
+    /// animation, then [`present`](Self::present) to finish.
+    pub fn present_transition(
+        &mut self,
+        transition: &Transition,
+        area: &Rectangle,
+        progress: f32,
+    ) -> Result<(), SPI::Error> {
+        let area = area.intersection(&self.bounding_box());
+        let topology = self.topology;
+        let Parts {
+            front,
+            back,
+            scratch,
+            ..
+        } = self.parts();
+
+        scratch.copy_from_slice(back);
+        for point in area.points() {
+            let color = match transition.sample(&area, point, progress) {
+                Sample::Old(from) => frame::pixel(front, &topology, from),
+                Sample::New(from) => frame::pixel(back, &topology, from),
+            };
+            frame::set_pixel(scratch, &topology, point, color);
+        }
+
+        self.send()
+    }
+
+    /// Sends the digits of the scratch frame that differ from what the chips show.
+    fn send(&mut self) -> Result<(), SPI::Error> {
+        let stale = core::mem::replace(&mut self.stale, false);
+        let Parts {
+            scratch,
+            shown,
+            segments,
+            settings,
+            frame,
+            ..
+        } = split(&mut self.buffer, self.topology.devices());
+
+        // 7-segment modules show their own digits instead of the frame
+        for ((digits, segments), settings) in
+            scratch.iter_mut().zip(segments.iter()).zip(settings.iter())
+        {
+            if Settings::module(settings) != Module::Matrix {
+                *digits = *segments;
+            }
+        }
+
+        send_changes(&mut self.spi, scratch, shown, frame, stale).inspect_err(|_| {
+            // No telling what made it out, start over on the next flush
+            self.stale = true;
+        })
+    }
+}
+
+/// Sends the digits of `source` that differ from `shown`, or all of them if `stale`, and
+/// records them in `shown`. Both hold one display per chain address.
+///
+/// `frame` is scratch space for one `[register, data]` pair per display. Each frame sent
+/// carries the next digit for every display that still has one, and a no-op for the rest.
+fn send_changes<SPI: Write<u8>>(
+    spi: &mut SPI,
+    source: &[[u8; MAX_DIGITS]],
+    shown: &mut [[u8; MAX_DIGITS]],
+    frame: &mut [u8],
+    stale: bool,
+) -> Result<(), SPI::Error> {
+    // Every round settles one digit per display, so eight of them cover any change
+    for round in 0..MAX_DIGITS {
+        let mut pending = false;
+
+        for ((pair, display), shown) in frame
+            .chunks_exact_mut(2)
+            .zip(source.iter())
+            .zip(shown.iter_mut())
+        {
+            let next = if stale {
+                Some(round)
+            } else {
+                (0..MAX_DIGITS).find(|digit| display[*digit] != shown[*digit])
+            };
+
+            match next {
+                // Register is the 1-based digit index
+                Some(digit) => {
+                    pair.copy_from_slice(&[digit as u8 + 1, display[digit]]);
+                    shown[digit] = display[digit];
+                    pending = true;
+                }
+                None => pair.copy_from_slice(&[Command::NoOp as u8, 0]),
+            }
+        }
+
+        if !pending {
+            break;
+        }
+
+        // Send the entire SPI buffer to all displays in the chain
+        spi.write(frame)?;
+    }
+
+    Ok(())
+}
+
+/// Draws into the back buffer, which stays off the panel until [`Driver::present`].
+impl<SPI, T, B> DrawTarget for Driver<SPI, T, B>
+where
+    SPI: Write<u8>,
+    T: Grid,
+    B: Storage,
+{
+    type Color = BinaryColor;
+    type Error = core::convert::Infallible;
+
+    fn draw_iter<I>(&mut self, pixels: I) -> Result<(), Self::Error>
+    where
+        I: IntoIterator<Item = Pixel<BinaryColor>>,
+    {
+        let bb = self.bounding_box();
+        let topology = self.topology;
+        let back = self.parts().back;
+
+        pixels
+            .into_iter()
+            .filter(|Pixel(pos, _color)| bb.contains(*pos))
+            .for_each(|Pixel(pos, color)| frame::set_pixel(back, &topology, pos, color));
+
+        Ok(())
+    }
+
+    fn clear(&mut self, color: Self::Color) -> Result<(), Self::Error> {
+        let fill = match color {
+            BinaryColor::On => 0xFF,
+            BinaryColor::Off => 0x00,
+        };
+        self.parts().back.fill([fill; MAX_DIGITS]);
+
+        Ok(())
+    }
+}
+
+impl<SPI, T, B> OriginDimensions for Driver<SPI, T, B>
+where
+    SPI: Write<u8>,
+    T: Grid,
+    B: Storage,
+{
+    fn size(&self) -> Size {
+        // The width is `cols * 8` and the height is `rows * 8`
+        Size::new(
+            (self.topology.cols() * 8) as u32,
+            (self.topology.rows() * 8) as u32,
+        )
+    }
+}
@@ -0,0 +1,62 @@
+/// Decimal point, added to a digit to light its DP segment in either decode mode.
+pub const DECIMAL_POINT: u8 = 0b1000_0000;
+
+pub(crate) const CODE_B_BLANK: u8 = 0x0F;
+
+/// Code B value showing `c` on a digit with Code B decoding, if the chip's font has it.
+///
+/// The font only knows `0`-`9`, `-`, `E`, `H`, `L`, `P` and blank.
+pub fn code_b_for(c: char) -> Option<u8> {
+    match c {
+        '0'..='9' => Some(c as u8 - b'0'),
+        '-' => Some(0x0A),
+        'E' | 'e' => Some(0x0B),
+        'H' | 'h' => Some(0x0C),
+        'L' | 'l' => Some(0x0D),
+        'P' | 'p' => Some(0x0E),
+        ' ' => Some(CODE_B_BLANK),
+        _ => None,
+    }
+}
+
+/// Segments lighting `c` on a digit without decoding, as `DP A B C D E F G` from the MSB.
+///
+/// Covers the digits and the letters that read well on seven segments; anything else
+/// comes out blank.
+pub fn segments_for(c: char) -> u8 {
+    match c.to_ascii_uppercase() {
+        '0' => 0b0111_1110,
+        '1' => 0b0011_0000,
+        '2' => 0b0110_1101,
+        '3' => 0b0111_1001,
+        '4' => 0b0011_0011,
+        '5' => 0b0101_1011,
+        '6' => 0b0101_1111,
+        '7' => 0b0111_0000,
+        '8' => 0b0111_1111,
+        '9' => 0b0111_1011,
+        'A' => 0b0111_0111,
+        'B' => 0b0001_1111,
+        'C' => 0b0100_1110,
+        'D' => 0b0011_1101,
+        'E' => 0b0100_1111,
+        'F' => 0b0100_0111,
+        'G' => 0b0101_1110,
+        'H' => 0b0011_0111,
+        'I' => 0b0000_0110,
+        'J' => 0b0011_1100,
+        'L' => 0b0000_1110,
+        'N' => 0b0001_0101,
+        'O' => 0b0001_1101,
+        'P' => 0b0110_0111,
+        'R' => 0b0000_0101,
+        'S' => 0b0101_1011,
+        'T' => 0b0000_1111,
+        'U' => 0b0011_1110,
+        'Y' => 0b0011_1011,
+        '-' => 0b0000_0001,
+        '_' => 0b0000_1000,
+        '=' => 0b0000_1001,
+        _ => 0,
+    }
+}
@@ -0,0 +1,203 @@
+use core::fmt;
+use core::str::FromStr;
+
+/// Corner of the grid holding chain address 0.
+///
+/// Address 0 is the module at the far end of the chain from the MCU: the first pair of
+/// every frame is shifted through all the others to reach it.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Corner {
+    TopLeft,
+    TopRight,
+    BottomLeft,
+    BottomRight,
+}
+
+/// How the chain moves on from one row of modules to the next.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Wiring {
+    /// Every row runs in the same direction, starting from the side of address 0.
+    Progressive,
+    /// Rows alternate direction, so the chain snakes back and forth across the grid.
+    Serpentine,
+}
+
+impl FromStr for Corner {
+    type Err = UnknownName;
+
+    /// Parses "top_left", "top_right", "bottom_left" or "bottom_right".
+    fn from_str(name: &str) -> Result<Self, Self::Err> {
+        match name {
+            "top_left" => Ok(Corner::TopLeft),
+            "top_right" => Ok(Corner::TopRight),
+            "bottom_left" => Ok(Corner::BottomLeft),
+            "bottom_right" => Ok(Corner::BottomRight),
+            _ => Err(UnknownName),
+        }
+    }
+}
+
+impl FromStr for Wiring {
+    type Err = UnknownName;
+
+    /// Parses "progressive" or "serpentine".
+    fn from_str(name: &str) -> Result<Self, Self::Err> {
+        match name {
+            "progressive" => Ok(Wiring::Progressive),
+            "serpentine" => Ok(Wiring::Serpentine),
+            _ => Err(UnknownName),
+        }
+    }
+}
+
+/// Error parsing a [`Corner`] or [`Wiring`] from a name it doesn't have.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct UnknownName;
+
+impl fmt::Display for UnknownName {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("unknown name")
+    }
+}
+
+/// Clockwise rotation of a module, relative to its digit rows running top to bottom.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Rotation {
+    #[default]
+    None,
+    Clockwise90,
+    Clockwise180,
+    Clockwise270,
+}
+
+/// How a single module is mounted.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Orientation {
+    pub rotation: Rotation,
+    /// The module's columns are mirrored left to right, before `rotation` is applied.
+    pub mirrored: bool,
+}
+
+/// A grid of modules the driver can map pixels onto, sized at compile time by [`Topology`]
+/// or at runtime by [`DynTopology`](crate::DynTopology).
+pub trait Grid: Copy {
+    /// Number of module rows.
+    fn rows(&self) -> usize;
+
+    /// Number of module columns.
+    fn cols(&self) -> usize;
+
+    /// Maps a panel pixel to the chain address of the module showing it, and the pixel's
+    /// `(column, digit)` in that module's own frame. Column 0 is the data MSB.
+    fn locate(&self, x: usize, y: usize) -> (usize, usize, usize);
+
+    /// Number of modules in the chain.
+    fn devices(&self) -> usize {
+        self.rows() * self.cols()
+    }
+}
+
+/// Physical layout of a `ROWS` x `COLS` grid of modules: where chain address 0 is, how
+/// the addresses run through the rows, and how each module is mounted.
+///
+/// Module positions are given as `(row, col)` on the panel, with `(0, 0)` top left.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Topology<const ROWS: usize, const COLS: usize> {
+    start: Corner,
+    wiring: Wiring,
+    orientations: [[Orientation; COLS]; ROWS],
+}
+
+impl<const ROWS: usize, const COLS: usize> Topology<ROWS, COLS> {
+    pub const fn new(start: Corner, wiring: Wiring) -> Self {
+        Topology {
+            start,
+            wiring,
+            orientations: [[Orientation {
+                rotation: Rotation::None,
+                mirrored: false,
+            }; COLS]; ROWS],
+        }
+    }
+
+    /// Sets how the module at `(row, col)` on the panel is mounted.
+    pub fn with_orientation(mut self, row: usize, col: usize, orientation: Orientation) -> Self {
+        self.orientations[row][col] = orientation;
+        self
+    }
+
+    /// See [`Grid::locate`].
+    pub fn locate(&self, x: usize, y: usize) -> (usize, usize, usize) {
+        let (row, col) = (y / 8, x / 8);
+        let addr = chain_address(ROWS, COLS, self.start, self.wiring, row, col);
+        let (column, digit) = unmount(self.orientations[row][col], x % 8, y % 8);
+
+        (addr, column, digit)
+    }
+}
+
+impl<const ROWS: usize, const COLS: usize> Grid for Topology<ROWS, COLS> {
+    fn rows(&self) -> usize {
+        ROWS
+    }
+
+    fn cols(&self) -> usize {
+        COLS
+    }
+
+    fn locate(&self, x: usize, y: usize) -> (usize, usize, usize) {
+        Topology::locate(self, x, y)
+    }
+}
+
+/// Chain address of the module at `(row, col)` on a `rows` x `cols` grid.
+pub(crate) fn chain_address(
+    rows: usize,
+    cols: usize,
+    start: Corner,
+    wiring: Wiring,
+    row: usize,
+    col: usize,
+) -> usize {
+    // How far along the chain this row of modules is
+    let chain_row = match start {
+        Corner::TopLeft | Corner::TopRight => row,
+        Corner::BottomLeft | Corner::BottomRight => rows - 1 - row,
+    };
+
+    // Rows run away from the starting side, unless a serpentine turns them around
+    let from_left = matches!(start, Corner::TopLeft | Corner::BottomLeft);
+    let reversed = wiring == Wiring::Serpentine && chain_row % 2 == 1;
+    let chain_col = if from_left != reversed {
+        col
+    } else {
+        cols - 1 - col
+    };
+
+    chain_row * cols + chain_col
+}
+
+/// Undoes the mounting of a module to get from a pixel within it back to the module's own
+/// `(column, digit)`.
+pub(crate) fn unmount(orientation: Orientation, local_x: usize, local_y: usize) -> (usize, usize) {
+    let (column, digit) = match orientation.rotation {
+        Rotation::None => (local_x, local_y),
+        Rotation::Clockwise90 => (local_y, 7 - local_x),
+        Rotation::Clockwise180 => (7 - local_x, 7 - local_y),
+        Rotation::Clockwise270 => (7 - local_y, local_x),
+    };
+    let column = if orientation.mirrored {
+        7 - column
+    } else {
+        column
+    };
+
+    (column, digit)
+}
+
+impl<const ROWS: usize, const COLS: usize> Default for Topology<ROWS, COLS> {
+    /// The original wiring: address 0 is bottom left and every row runs left to right.
+    fn default() -> Self {
+        Topology::new(Corner::BottomLeft, Wiring::Progressive)
+    }
+}
@@ -0,0 +1,92 @@
+use embedded_graphics_core::prelude::{Point, Size};
+use embedded_graphics_core::primitives::Rectangle;
+
+/// Animation from the frame on the panel to the next one, played with
+/// [`Driver::present_transition`](crate::Driver::present_transition).
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Transition {
+    /// Every band of `row_height` pixels rolls up, the new one coming in from below.
+    Roll { row_height: u32 },
+    /// The new frame sweeps in from the left.
+    Wipe,
+    /// The new frame shows through pixel by pixel, in a fixed scattered order.
+    Dissolve,
+    /// Every `cell` sized character flips over top to bottom, one after the other from
+    /// the left, like a split-flap board. Cells that didn't change stay still.
+    SplitFlap { cell: Size },
+}
+
+/// Which frame a pixel is taken from during a transition, and where in it.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Sample {
+    Old(Point),
+    New(Point),
+}
+
+impl Transition {
+    /// Where the pixel at `point`, inside `area`, comes from `progress` of the way through
+    /// the transition, from 0.0 (all old) to 1.0 (all new).
+    pub fn sample(&self, area: &Rectangle, point: Point, progress: f32) -> Sample {
+        let progress = progress.clamp(0.0, 1.0);
+        let local = point - area.top_left;
+        let (x, y) = (local.x as u32, local.y as u32);
+
+        match *self {
+            Transition::Roll { row_height } => {
+                let row_height = row_height.max(1);
+                let row_top = y - y % row_height;
+                // The last band can be cut short by the area
+                let height = row_height.min(area.size.height - row_top);
+                let shift = (progress * height as f32 + 0.5) as u32;
+                let source = y - row_top + shift;
+
+                if source < height {
+                    Sample::Old(area.top_left + Point::new(x as i32, (row_top + source) as i32))
+                } else {
+                    Sample::New(
+                        area.top_left + Point::new(x as i32, (row_top + source - height) as i32),
+                    )
+                }
+            }
+            Transition::Wipe => {
+                let edge = (progress * area.size.width as f32 + 0.5) as u32;
+                if x < edge {
+                    Sample::New(point)
+                } else {
+                    Sample::Old(point)
+                }
+            }
+            Transition::Dissolve => {
+                if (scatter(x, y) as f32) < progress * 256.0 {
+                    Sample::New(point)
+                } else {
+                    Sample::Old(point)
+                }
+            }
+            Transition::SplitFlap { cell } => {
+                let (width, height) = (cell.width.max(1), cell.height.max(1));
+                let cells = area.size.width.div_ceil(width);
+
+                // Flips start one after the other over the first half, each taking half
+                let start = (x / width) as f32 / cells as f32 * 0.5;
+                let flipped = ((progress - start) * 2.0).clamp(0.0, 1.0);
+                let fold = (flipped * height as f32 + 0.5) as u32;
+
+                if y % height < fold {
+                    Sample::New(point)
+                } else {
+                    Sample::Old(point)
+                }
+            }
+        }
+    }
+}
+
+/// Spreads the pixels of a frame evenly over 0-255, in an order that looks random.
+fn scatter(x: u32, y: u32) -> u8 {
+    let mut hash = x.wrapping_mul(0x9E37_79B1) ^ y.wrapping_mul(0x85EB_CA77);
+    hash ^= hash >> 15;
+    hash = hash.wrapping_mul(0x2C1B_3C6D);
+    hash ^= hash >> 12;
+    (hash >> 24) as u8
+}
@@ -0,0 +1,9 @@
+#!/usr/bin/env bash
+
+set -e
+
+# The simulator runs on the host, so override the ESP32-C3 target from .cargo/config.toml
+HOST_TARGET=$(rustc -vV | sed -n 's|host: ||p')
+
+cd simulator
+cargo run --target "${HOST_TARGET}" -- "$@"
@@ -0,0 +1,120 @@
+const MAX_DIGITS: usize = 8;
+
+/// Register file of one MAX7219.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Registers {
+    pub digits: [u8; MAX_DIGITS],
+    pub decode_mode: u8,
+    pub intensity: u8,
+    pub scan_limit: u8,
+    pub shutdown: bool,
+    pub display_test: bool,
+}
+
+impl Default for Registers {
+    /// The chip powers up blanked, in shutdown and scanning a single digit.
+    fn default() -> Self {
+        Registers {
+            digits: [0; MAX_DIGITS],
+            decode_mode: 0,
+            intensity: 0,
+            scan_limit: 0,
+            shutdown: true,
+            display_test: false,
+        }
+    }
+}
+
+impl Registers {
+    fn latch(&mut self, register: u8, data: u8) {
+        // Only the low nibble of the address byte is decoded by the chip
+        match register & 0x0F {
+            digit @ 0x01..=0x08 => self.digits[(digit - 1) as usize] = data,
+            0x09 => self.decode_mode = data,
+            0x0A => self.intensity = data & 0x0F,
+            0x0B => self.scan_limit = data & 0x07,
+            0x0C => self.shutdown = data & 0x01 == 0,
+            0x0F => self.display_test = data & 0x01 != 0,
+            _ => {}
+        }
+    }
+
+    /// The LEDs actually lit on `digit`, taking shutdown, scan limit and test mode into account.
+    pub fn lit(&self, digit: usize) -> u8 {
+        if self.display_test {
+            0xFF
+        } else if self.shutdown || digit > self.scan_limit as usize {
+            0x00
+        } else {
+            self.digits[digit]
+        }
+    }
+}
+
+/// A register write that reached a chip, as opposed to the no-op padding around it.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct RegisterWrite {
+    pub addr: usize,
+    pub register: u8,
+    pub data: u8,
+}
+
+/// A daisy chain of MAX7219s sharing one chip select.
+///
+/// Addresses follow the driver: pair `i` of a full frame is latched by chip `i`.
+pub struct Chain {
+    devices: Vec<Registers>,
+    // Bytes currently sitting in the chain's shift registers, in address order.
+    shift: Vec<u8>,
+}
+
+impl Chain {
+    pub fn new(devices: usize) -> Self {
+        Chain {
+            devices: vec![Registers::default(); devices],
+            shift: vec![0; devices * 2],
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.devices.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.devices.is_empty()
+    }
+
+    pub fn device(&self, addr: usize) -> &Registers {
+        &self.devices[addr]
+    }
+
+    /// Clocks `words` into the chain and latches every chip, like a CS rising edge would.
+    ///
+    /// Frames shorter than the chain leave stale bytes in the chips furthest along,
+    /// which then latch them again, exactly like the hardware does.
+    pub fn latch(&mut self, words: &[u8]) -> Vec<RegisterWrite> {
+        let len = self.shift.len();
+        self.shift.extend_from_slice(words);
+        self.shift.drain(..self.shift.len() - len);
+
+        let mut writes = Vec::new();
+        for (addr, (device, pair)) in self
+            .devices
+            .iter_mut()
+            .zip(self.shift.chunks_exact(2))
+            .enumerate()
+        {
+            // No-op pairs only pad the frame out to the other chips
+            if pair[0] & 0x0F != 0x00 {
+                device.latch(pair[0], pair[1]);
+                writes.push(RegisterWrite {
+                    addr,
+                    register: pair[0],
+                    data: pair[1],
+                });
+            }
+        }
+
+        writes
+    }
+}
@@ -0,0 +1,28 @@
+// The drawing code is compiled straight from the firmware sources, so whatever
+// shows up here is exactly what gets shifted out to the real wall.
+#[path = "../../src/animation.rs"]
+pub mod animation;
+#[path = "../../src/board.rs"]
+pub mod board;
+#[path = "../../src/departure.rs"]
+pub mod departure;
+#[path = "../../src/filter.rs"]
+pub mod filter;
+#[path = "../../src/json.rs"]
+pub mod json;
+#[path = "../../src/layout.rs"]
+pub mod layout;
+#[path = "../../src/marquee.rs"]
+pub mod marquee;
+#[path = "../../src/schedule.rs"]
+pub mod schedule;
+#[path = "../../src/screen.rs"]
+pub mod screen;
+#[path = "../../src/stops.rs"]
+pub mod stops;
+#[path = "../../src/timezone.rs"]
+pub mod timezone;
+
+pub mod chain;
+pub mod mock;
+pub mod panel;
@@ -0,0 +1,260 @@
+use anyhow::Result as ResultAny;
+use chrono::{DateTime, TimeDelta, Utc};
+use chrono_tz::Tz;
+use embedded_graphics::geometry::AnchorPoint;
+use embedded_graphics::pixelcolor::BinaryColor;
+use embedded_graphics::prelude::{Dimensions, DrawTarget};
+use matrix_simulator::animation::Animation;
+use matrix_simulator::board::{self, HourFormat};
+use matrix_simulator::departure::{Departure, Mode};
+use matrix_simulator::panel::PanelSpi;
+use matrix_simulator::screen::{
+    parse_rotation, ClockScreen, Departures, DeparturesScreen, MessageScreen, Rotation, ScreenKind,
+    Weather, WeatherScreen,
+};
+use matrix_simulator::stops::parse_stops;
+use max7219::{Corner, DynMax7219, DynTopology, Wiring};
+use std::io::{self, Write};
+use std::thread;
+use std::time::{Duration, Instant};
+
+// Same pacing as the firmware
+const FRAME_INTERVAL: Duration = Duration::from_millis(50);
+const SCROLL_SPEED: u32 = 20;
+const TRANSITION_FRAMES: u32 = 12;
+const SPLASH: &[u8] = include_bytes!("../../assets/splash.gif");
+const STILL_SPLASH: Duration = Duration::from_secs(2);
+/// The stops in the firmware's default config.
+const STOPS: &str = "8220IR3881 | Killester | TRAIN_STATION | KI | walk=9; \
+    8220DB000529 | Collins Avenue, Killester | BUS_STOP | CA | walk=4; \
+    8220DB000609 | Castle Grove, Clontarf | BUS_STOP | CG | walk=2";
+
+/// Stands in for `wifi::post_with_time`: three departures a few minutes apart, with
+/// the first one drifting as time goes by and the second running a couple of minutes late.
+fn fake_departures(now: DateTime<Tz>, mode: Mode, services: [&str; 3], offset: i64) -> Departures {
+    let mut departures = [None, None, None];
+    for (i, service) in services.iter().enumerate() {
+        let minutes = (offset + 7 * i as i64) - (now.timestamp() / 60) % 5;
+        let scheduled = (now + TimeDelta::minutes(minutes)).fixed_offset();
+        let (service_number, destination) = match mode {
+            Mode::Train => ("DART", *service),
+            _ => (*service, "Dublin"),
+        };
+        departures[i] = Some(Departure {
+            service_number: service_number.to_string(),
+            destination: destination.to_string(),
+            mode,
+            scheduled_departure: scheduled,
+            real_time_departure: Some(scheduled + TimeDelta::minutes(if i == 1 { 2 } else { 0 })),
+            ..Default::default()
+        });
+    }
+    departures
+}
+
+fn main() -> ResultAny<()> {
+    // `--once` draws a single frame and exits, handy for diffing layouts
+    let once = std::env::args().any(|arg| arg == "--once");
+
+    // `--rows=N`, `--cols=N`, `--corner=NAME` and `--wiring=NAME` describe another panel,
+    // like `panel_rows`, `panel_cols`, `panel_corner` and `panel_wiring` in cfg.toml
+    let rows = std::env::args()
+        .find_map(|arg| arg.strip_prefix("--rows=").map(String::from))
+        .unwrap_or_else(|| "3".to_string());
+    let rows: usize = rows
+        .parse()
+        .map_err(|e| anyhow::anyhow!("Bad panel rows {}: {}", rows, e))?;
+    let cols = std::env::args()
+        .find_map(|arg| arg.strip_prefix("--cols=").map(String::from))
+        .unwrap_or_else(|| "15".to_string());
+    let cols: usize = cols
+        .parse()
+        .map_err(|e| anyhow::anyhow!("Bad panel cols {}: {}", cols, e))?;
+    let corner = std::env::args()
+        .find_map(|arg| arg.strip_prefix("--corner=").map(String::from))
+        .unwrap_or_else(|| "bottom_left".to_string());
+    let corner: Corner = corner
+        .parse()
+        .map_err(|e| anyhow::anyhow!("Unknown panel corner {}: {}", corner, e))?;
+    let wiring = std::env::args()
+        .find_map(|arg| arg.strip_prefix("--wiring=").map(String::from))
+        .unwrap_or_else(|| "progressive".to_string());
+    let wiring: Wiring = wiring
+        .parse()
+        .map_err(|e| anyhow::anyhow!("Unknown panel wiring {}: {}", wiring, e))?;
+
+    // Same driver and buffer as the firmware
+    let topology = DynTopology::new(rows, cols, corner, wiring);
+    let spi = PanelSpi::new(topology);
+    let buffer = vec![[0; 8]; topology.buffer_len()];
+    let mut display = DynMax7219::new(spi.clone(), topology, buffer);
+
+    // make sure to wake the display up
+    display.init()?;
+    display.power_on()?;
+
+    let mut stdout = io::stdout();
+
+    // `--image=PATH` plays a BMP, PBM or GIF from disk instead of the built in splash,
+    // like `splash_url` in cfg.toml
+    let image = std::env::args().find_map(|arg| arg.strip_prefix("--image=").map(String::from));
+    if !once || image.is_some() {
+        let splash = match image {
+            Some(path) => Animation::decode(&std::fs::read(path)?)?,
+            None => Animation::decode(SPLASH)?,
+        };
+        let position = display
+            .bounding_box()
+            .resized(splash.size(), AnchorPoint::Center)
+            .top_left;
+        for frame in splash.frames() {
+            display.clear(BinaryColor::Off)?;
+            frame.draw(&mut display, position)?;
+            display.present()?;
+            write!(stdout, "\x1b[2J\x1b[H")?;
+            spi.panel().render(&mut stdout)?;
+            thread::sleep(match frame.delay() {
+                Duration::ZERO => STILL_SPLASH,
+                delay => delay,
+            });
+        }
+    }
+
+    // `--stops=LIST` shows other stops, like `stops` in cfg.toml, with made up services:
+    // trains to the usual destinations and buses on the routes past Killester
+    let stops = parse_stops(
+        &std::env::args()
+            .find_map(|arg| arg.strip_prefix("--stops=").map(String::from))
+            .unwrap_or_else(|| STOPS.to_string()),
+    )?;
+    let mut routes = [["27", "27B", "N4"], ["130", "130", "130"]]
+        .into_iter()
+        .cycle();
+    let locations: Vec<_> = stops
+        .iter()
+        .map(|stop| match stop.stop_type.as_str() {
+            "TRAIN_STATION" => (
+                stop.label.clone(),
+                Mode::Train,
+                ["Greystones", "Bray (Daly)", "Dublin Connolly"],
+                6,
+            ),
+            _ => (
+                stop.label.clone(),
+                Mode::Bus,
+                routes.next().unwrap(),
+                2 * stop.row as i64,
+            ),
+        })
+        .collect();
+    let bounds = display.bounding_box();
+
+    // `--screens=LIST` picks the screens to rotate through, like `screens` in cfg.toml
+    let screens = std::env::args()
+        .find_map(|arg| arg.strip_prefix("--screens=").map(String::from))
+        .unwrap_or_else(|| "departures:30, clock:10, weather:10, message".to_string());
+    // `--12h` counts the hours to 12 with AM and PM, like `clock_24h = false` in cfg.toml
+    let hours = match std::env::args().any(|arg| arg == "--12h") {
+        true => HourFormat::Twelve,
+        false => HourFormat::TwentyFour,
+    };
+    // `--timezone=NAME` shows local time somewhere else, like `timezone` in cfg.toml
+    let timezone_name = std::env::args()
+        .find_map(|arg| arg.strip_prefix("--timezone=").map(String::from))
+        .unwrap_or_else(|| "Europe/Dublin".to_string());
+    let timezone: Tz = timezone_name
+        .parse()
+        .map_err(|e| anyhow::anyhow!("Unknown timezone {}: {}", timezone_name, e))?;
+    let mut rotation = Rotation::new();
+    for slot in parse_rotation(&screens)? {
+        rotation = match slot.kind {
+            // The fake departures never fail, but taller panels still get a status bar
+            ScreenKind::Departures => rotation.with_screen(
+                DeparturesScreen::new(board::layout(bounds, locations.len()), {
+                    let locations = locations.clone();
+                    move |now| {
+                        let rows = locations.iter().map(|(label, mode, services, offset)| {
+                            (
+                                label.clone(),
+                                fake_departures(now, *mode, *services, *offset),
+                            )
+                        });
+                        Some(rows.collect())
+                    }
+                })
+                .with_speed(SCROLL_SPEED)
+                .with_hour_format(hours)
+                .with_walk_times(stops.iter().map(|stop| stop.walk).collect()),
+                slot.dwell,
+            ),
+            ScreenKind::Clock => rotation.with_screen(
+                ClockScreen::new(timezone).with_hour_format(hours),
+                slot.dwell,
+            ),
+            ScreenKind::Weather => rotation.with_screen(
+                WeatherScreen::new(|| {
+                    Some(Weather {
+                        temperature: 11.4,
+                        code: 61,
+                    })
+                })
+                .with_speed(SCROLL_SPEED),
+                slot.dwell,
+            ),
+            ScreenKind::Message => rotation.with_screen(
+                MessageScreen::new("Mind the gap\nbetween the train and the platform", bounds)
+                    .with_speed(SCROLL_SPEED),
+                slot.dwell,
+            ),
+        };
+    }
+    let started = Instant::now();
+
+    // `--transition=NAME` picks the animation between updates, like `transition` in cfg.toml
+    let transition = board::transition(
+        &std::env::args()
+            .find_map(|arg| arg.strip_prefix("--transition=").map(String::from))
+            .unwrap_or_else(|| "roll".to_string()),
+    );
+
+    loop {
+        let current_time = Utc::now().with_timezone(&timezone);
+
+        let uptime = started.elapsed();
+        let switched = rotation.advance(uptime);
+        let elapsed = rotation.elapsed(uptime);
+        let mut refresh = switched;
+
+        display.clear(BinaryColor::Off)?;
+        if let Some(screen) = rotation.current() {
+            refresh |= screen.update(current_time);
+            screen.draw(&mut display, current_time, elapsed)?;
+        }
+
+        // Animate to the next screen or the new departures, the first frame comes up straight
+        if let (true, false, Some(transition)) = (refresh, once, transition) {
+            let area = display.bounding_box();
+            for step in 1..TRANSITION_FRAMES {
+                let progress = step as f32 / TRANSITION_FRAMES as f32;
+                display.present_transition(&transition, &area, progress)?;
+                write!(stdout, "\x1b[2J\x1b[H")?;
+                spi.panel().render(&mut stdout)?;
+                thread::sleep(FRAME_INTERVAL);
+            }
+        }
+
+        display.present()?;
+
+        if !once {
+            // Home the cursor so every frame is drawn over the previous one
+            write!(stdout, "\x1b[2J\x1b[H")?;
+        }
+        spi.panel().render(&mut stdout)?;
+
+        if once {
+            return Ok(());
+        }
+
+        thread::sleep(FRAME_INTERVAL);
+    }
+}
@@ -0,0 +1,74 @@
+use crate::chain::{Chain, RegisterWrite, Registers};
+use embedded_hal::blocking::spi::Write;
+use std::cell::RefCell;
+use std::convert::Infallible;
+use std::rc::Rc;
+
+struct Recording {
+    chain: Chain,
+    transactions: Vec<Vec<u8>>,
+    writes: Vec<RegisterWrite>,
+}
+
+/// SPI mock that records every transaction sent to a MAX7219 chain and decodes
+/// what each chip received.
+///
+/// Clones share the same recording, so keep one handle for assertions and give
+/// the other one to the driver.
+#[derive(Clone)]
+pub struct RecordingSpi(Rc<RefCell<Recording>>);
+
+impl RecordingSpi {
+    pub fn new(devices: usize) -> Self {
+        RecordingSpi(Rc::new(RefCell::new(Recording {
+            chain: Chain::new(devices),
+            transactions: Vec::new(),
+            writes: Vec::new(),
+        })))
+    }
+
+    /// Raw bytes of every `write` call, one entry per chip select cycle.
+    pub fn transactions(&self) -> Vec<Vec<u8>> {
+        self.0.borrow().transactions.clone()
+    }
+
+    /// Every register write that reached a chip, in order, with no-op padding left out.
+    pub fn writes(&self) -> Vec<RegisterWrite> {
+        self.0.borrow().writes.clone()
+    }
+
+    /// `(register, data)` pairs latched by the chip at `addr`, in order.
+    pub fn writes_to(&self, addr: usize) -> Vec<(u8, u8)> {
+        self.0
+            .borrow()
+            .writes
+            .iter()
+            .filter(|write| write.addr == addr)
+            .map(|write| (write.register, write.data))
+            .collect()
+    }
+
+    /// Current register state of the chip at `addr`.
+    pub fn device(&self, addr: usize) -> Registers {
+        *self.0.borrow().chain.device(addr)
+    }
+
+    /// Forgets the recorded traffic but keeps the chips' register state.
+    pub fn reset_log(&self) {
+        let mut recording = self.0.borrow_mut();
+        recording.transactions.clear();
+        recording.writes.clear();
+    }
+}
+
+impl Write<u8> for RecordingSpi {
+    type Error = Infallible;
+
+    fn write(&mut self, words: &[u8]) -> Result<(), Self::Error> {
+        let mut recording = self.0.borrow_mut();
+        recording.transactions.push(words.to_vec());
+        let writes = recording.chain.latch(words);
+        recording.writes.extend(writes);
+        Ok(())
+    }
+}
@@ -0,0 +1,73 @@
+use crate::chain::Chain;
+use embedded_hal::blocking::spi::Write;
+use max7219::DynTopology;
+use std::cell::{Ref, RefCell};
+use std::convert::Infallible;
+use std::io;
+use std::rc::Rc;
+
+/// A wall of 8x8 modules, sized and wired as described by its [`DynTopology`].
+pub struct Panel {
+    topology: DynTopology<'static>,
+    chain: Chain,
+}
+
+impl Panel {
+    pub fn new(topology: DynTopology<'static>) -> Self {
+        Panel {
+            topology,
+            chain: Chain::new(topology.devices()),
+        }
+    }
+
+    fn pixel(&self, x: usize, y: usize) -> bool {
+        let (addr, column, digit) = self.topology.locate(x, y);
+        self.chain.device(addr).lit(digit) & (1 << (7 - column)) != 0
+    }
+
+    /// Draws the wall with half blocks, two pixel rows per terminal line.
+    pub fn render<W: io::Write>(&self, out: &mut W) -> io::Result<()> {
+        let width = self.topology.cols() * 8;
+        let height = self.topology.rows() * 8;
+
+        writeln!(out, "┌{}┐", "─".repeat(width))?;
+        for y in (0..height).step_by(2) {
+            let line: String = (0..width)
+                .map(|x| match (self.pixel(x, y), self.pixel(x, y + 1)) {
+                    (false, false) => ' ',
+                    (true, false) => '▀',
+                    (false, true) => '▄',
+                    (true, true) => '█',
+                })
+                .collect();
+            writeln!(out, "│{}│", line)?;
+        }
+        writeln!(out, "└{}┘", "─".repeat(width))?;
+
+        out.flush()
+    }
+}
+
+/// SPI sink standing in for the MAX7219 chain. Clones share the same [`Panel`], so
+/// one handle can be given to the driver and the other used to render.
+#[derive(Clone)]
+pub struct PanelSpi(Rc<RefCell<Panel>>);
+
+impl PanelSpi {
+    pub fn new(topology: DynTopology<'static>) -> Self {
+        PanelSpi(Rc::new(RefCell::new(Panel::new(topology))))
+    }
+
+    pub fn panel(&self) -> Ref<'_, Panel> {
+        self.0.borrow()
+    }
+}
+
+impl Write<u8> for PanelSpi {
+    type Error = Infallible;
+
+    fn write(&mut self, words: &[u8]) -> Result<(), Self::Error> {
+        self.0.borrow_mut().chain.latch(words);
+        Ok(())
+    }
+}
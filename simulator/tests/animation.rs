@@ -0,0 +1,210 @@
+use embedded_graphics::mock_display::MockDisplay;
+use embedded_graphics::pixelcolor::BinaryColor;
+use embedded_graphics::prelude::{Point, Size};
+use matrix_simulator::animation::{Animation, ImageError, MAX_FRAMES};
+use std::time::Duration;
+
+fn draw(animation: &Animation, elapsed: Duration) -> MockDisplay<BinaryColor> {
+    let mut display = MockDisplay::new();
+    animation
+        .draw(&mut display, Point::zero(), elapsed)
+        .unwrap();
+    display
+}
+
+/// A GIF with a two color palette, black and white, one frame per pattern of `0`s and
+/// `1`s with its delay in hundredths of a second.
+fn gif(width: u16, frames: &[(&[u8], u16)]) -> Vec<u8> {
+    let height = (frames[0].0.len() / width as usize) as u16;
+    let mut data = Vec::new();
+    {
+        let palette = [0, 0, 0, 0xff, 0xff, 0xff];
+        let mut encoder = gif::Encoder::new(&mut data, width, height, &palette).unwrap();
+        for (pixels, delay) in frames {
+            let mut frame = gif::Frame::from_indexed_pixels(width, height, pixels.to_vec(), None);
+            frame.delay = *delay;
+            encoder.write_frame(&frame).unwrap();
+        }
+    }
+    data
+}
+
+/// A 24 bit BMP, bottom row first as BMP stores it, from rows of `#` and `.`.
+fn bmp(rows: &[&str]) -> Vec<u8> {
+    let (width, height) = (rows[0].len() as u32, rows.len() as u32);
+    let stride = (width * 3).div_ceil(4) * 4;
+    let mut data = Vec::new();
+    data.extend_from_slice(b"BM");
+    data.extend_from_slice(&(54 + stride * height).to_le_bytes());
+    data.extend_from_slice(&[0; 4]);
+    data.extend_from_slice(&54u32.to_le_bytes());
+    data.extend_from_slice(&40u32.to_le_bytes());
+    data.extend_from_slice(&width.to_le_bytes());
+    data.extend_from_slice(&height.to_le_bytes());
+    data.extend_from_slice(&1u16.to_le_bytes());
+    data.extend_from_slice(&24u16.to_le_bytes());
+    data.extend_from_slice(&[0; 24]);
+    for row in rows.iter().rev() {
+        let mut line: Vec<u8> = row
+            .chars()
+            .flat_map(|c| match c {
+                '#' => [0xc0, 0xc0, 0xc0],
+                _ => [0x30, 0x30, 0x30],
+            })
+            .collect();
+        line.resize(stride as usize, 0);
+        data.extend_from_slice(&line);
+    }
+    data
+}
+
+#[test]
+fn pbm_lights_up_the_white_pixels() {
+    let plain = Animation::decode(b"P1\n# a corner\n3 2\n0 1 1\n1 1 0\n").unwrap();
+    assert_eq!(plain.size(), Size::new(3, 2));
+    assert_eq!(plain.frames().len(), 1);
+    draw(&plain, Duration::ZERO).assert_pattern(&[
+        "#..", //
+        "..#", //
+    ]);
+
+    // The raw form packs the same rows into bytes, each padded out to a whole byte
+    let raw = Animation::decode(b"P4 3 2\n\x7f\xdf").unwrap();
+    assert_eq!(raw, plain);
+}
+
+#[test]
+fn bmp_is_thresholded_at_mid_gray() {
+    let animation = Animation::decode(&bmp(&["#..", ".#.", "..#"])).unwrap();
+
+    assert_eq!(animation.duration(), Duration::ZERO);
+    draw(&animation, Duration::from_secs(5)).assert_pattern(&[
+        "#..", //
+        ".#.", //
+        "..#", //
+    ]);
+}
+
+#[test]
+fn gif_frames_keep_their_own_delays() {
+    let data = gif(
+        2,
+        &[(&[1, 0, 0, 0], 5), (&[0, 1, 0, 0], 0), (&[0, 0, 0, 1], 20)],
+    );
+    let animation = Animation::decode(&data).unwrap();
+
+    let delays: Vec<_> = animation
+        .frames()
+        .iter()
+        .map(|frame| frame.delay())
+        .collect();
+    assert_eq!(
+        delays,
+        [
+            Duration::from_millis(50),
+            // Frames without a delay get the same one browsers give them
+            Duration::from_millis(100),
+            Duration::from_millis(200),
+        ]
+    );
+    assert_eq!(animation.duration(), Duration::from_millis(350));
+
+    draw(&animation, Duration::from_millis(49)).assert_pattern(&["#.", ".."]);
+    draw(&animation, Duration::from_millis(50)).assert_pattern(&[".#", ".."]);
+    draw(&animation, Duration::from_millis(200)).assert_pattern(&["..", ".#"]);
+    // And round again
+    draw(&animation, Duration::from_millis(360)).assert_pattern(&["#.", ".."]);
+}
+
+#[test]
+fn splash_is_a_short_animation() {
+    let splash = Animation::decode(include_bytes!("../../assets/splash.gif")).unwrap();
+
+    assert_eq!(splash.size(), Size::new(120, 24));
+    assert!(splash.frames().len() > 1);
+    assert!(splash.duration() <= Duration::from_secs(3));
+}
+
+#[test]
+fn other_files_are_turned_away() {
+    assert!(matches!(
+        Animation::decode(b"\x89PNG\r\n"),
+        Err(ImageError::UnknownFormat)
+    ));
+    assert!(matches!(
+        Animation::decode(b"P1 4 4\n0 1"),
+        Err(ImageError::Pbm(_))
+    ));
+    assert!(matches!(
+        Animation::decode(b"GIF89a"),
+        Err(ImageError::Gif(_))
+    ));
+}
+
+#[test]
+fn images_too_large_to_hold_are_turned_away() {
+    // Big enough to overflow when the sides are multiplied
+    assert!(matches!(
+        Animation::decode(b"P1 70000 70000 0"),
+        Err(ImageError::TooLarge(size)) if size == Size::new(70000, 70000)
+    ));
+    assert!(matches!(
+        Animation::decode(b"P4 4096 4096\n"),
+        Err(ImageError::TooLarge(_))
+    ));
+
+    // A GIF only says how big its screen is, the one frame can be tiny
+    let mut data = Vec::new();
+    {
+        let palette = [0, 0, 0, 0xff, 0xff, 0xff];
+        let mut encoder = gif::Encoder::new(&mut data, 1000, 1000, &palette).unwrap();
+        let frame = gif::Frame::from_indexed_pixels(1, 1, vec![1], None);
+        encoder.write_frame(&frame).unwrap();
+    }
+    assert!(matches!(
+        Animation::decode(&data),
+        Err(ImageError::TooLarge(_))
+    ));
+}
+
+/// A GIF with a `width` x `height` screen and `count` one pixel frames on it.
+fn tiny_frames(width: u16, height: u16, count: usize) -> Vec<u8> {
+    let mut data = Vec::new();
+    {
+        let palette = [0, 0, 0, 0xff, 0xff, 0xff];
+        let mut encoder = gif::Encoder::new(&mut data, width, height, &palette).unwrap();
+        for _ in 0..count {
+            let frame = gif::Frame::from_indexed_pixels(1, 1, vec![1], None);
+            encoder.write_frame(&frame).unwrap();
+        }
+    }
+    data
+}
+
+#[test]
+fn animations_too_long_to_hold_are_turned_away() {
+    assert_eq!(
+        Animation::decode(&tiny_frames(8, 8, MAX_FRAMES))
+            .unwrap()
+            .frames()
+            .len(),
+        MAX_FRAMES
+    );
+    assert!(matches!(
+        Animation::decode(&tiny_frames(8, 8, MAX_FRAMES + 1)),
+        Err(ImageError::TooManyFrames)
+    ));
+
+    // Every frame of a 256x256 screen takes 8 KiB, whatever size the GIF stores it at
+    assert_eq!(
+        Animation::decode(&tiny_frames(256, 256, 8))
+            .unwrap()
+            .frames()
+            .len(),
+        8
+    );
+    assert!(matches!(
+        Animation::decode(&tiny_frames(256, 256, 9)),
+        Err(ImageError::TooMuchData)
+    ));
+}
@@ -0,0 +1,114 @@
+//! Fixtures shared by the test files, each of which pulls them in with `mod common;`.
+
+// Every test file only uses some of them
+#![allow(dead_code)]
+
+use chrono::DateTime;
+use chrono_tz::Tz;
+use embedded_graphics::pixelcolor::BinaryColor;
+use embedded_graphics::prelude::{Dimensions, Point, PointsIter};
+use matrix_simulator::departure::{Departure, Mode};
+use matrix_simulator::json::{ArrayReader, JsonError};
+use matrix_simulator::mock::RecordingSpi;
+use max7219::{DynMax7219, DynTopology, FrameBuffer, Max7219, Topology};
+use serde::de::DeserializeOwned;
+use std::convert::Infallible;
+
+/// Grid of the drivers under test.
+pub const ROWS: usize = 2;
+pub const COLS: usize = 3;
+pub const DEVICES: usize = ROWS * COLS;
+
+pub fn display() -> (Max7219<RecordingSpi, ROWS, COLS>, RecordingSpi) {
+    display_with(Topology::default())
+}
+
+pub fn display_with(
+    topology: Topology<ROWS, COLS>,
+) -> (Max7219<RecordingSpi, ROWS, COLS>, RecordingSpi) {
+    let spi = RecordingSpi::new(DEVICES);
+    (Max7219::new(spi.clone(), topology), spi)
+}
+
+/// A [`DynMax7219`] for `topology`, with a buffer just big enough.
+pub fn dyn_display(
+    topology: DynTopology<'_>,
+) -> (DynMax7219<'_, RecordingSpi, Vec<[u8; 8]>>, RecordingSpi) {
+    let spi = RecordingSpi::new(topology.devices());
+    let buffer = vec![[0; 8]; topology.buffer_len()];
+    (DynMax7219::new(spi.clone(), topology, buffer), spi)
+}
+
+/// A frame the size of the real wall.
+pub type Wall = FrameBuffer<3, 15>;
+
+pub fn wall() -> Wall {
+    FrameBuffer::new(Topology::default())
+}
+
+/// Every lit pixel of `frame`.
+pub fn lit(frame: &Wall) -> Vec<Point> {
+    frame
+        .bounding_box()
+        .points()
+        .filter(|point| frame.pixel(*point) == BinaryColor::On)
+        .collect()
+}
+
+/// A bus on `route` timetabled to leave at `leaves`.
+pub fn bus(route: &str, leaves: DateTime<Tz>) -> Option<Departure> {
+    Some(Departure {
+        service_number: route.to_string(),
+        mode: Mode::Bus,
+        scheduled_departure: leaves.fixed_offset(),
+        ..Default::default()
+    })
+}
+
+/// A train to `destination` timetabled to leave at `leaves`.
+pub fn train(destination: &str, leaves: DateTime<Tz>) -> Option<Departure> {
+    Some(Departure {
+        destination: destination.to_string(),
+        mode: Mode::Train,
+        scheduled_departure: leaves.fixed_offset(),
+        ..Default::default()
+    })
+}
+
+/// Responses saved from the departures API, at 08:00 Irish time.
+pub const KILLESTER: &str = include_str!("../data/killester.json");
+pub const COLLINS_AVENUE: &str = include_str!("../data/collins_avenue.json");
+
+/// Reads `body` with `reader` the way a connection hands it over, `chunk` bytes at a
+/// time, and counts how much of it was read.
+pub fn read<T: DeserializeOwned>(
+    reader: ArrayReader,
+    body: &str,
+    chunk: usize,
+) -> (Result<Vec<T>, JsonError<Infallible>>, usize) {
+    let mut rest = body.as_bytes();
+    let mut consumed = 0;
+    let items = reader.read(|buf: &mut [u8]| {
+        let len = buf.len().min(chunk).min(rest.len());
+        buf[..len].copy_from_slice(&rest[..len]);
+        rest = &rest[len..];
+        consumed += len;
+        Ok(len)
+    });
+    (items, consumed)
+}
+
+/// The items of the departures array in `body`, read like the firmware reads them.
+pub fn stop_departures<T: DeserializeOwned>(body: &str) -> Result<Vec<T>, JsonError<Infallible>> {
+    read(
+        ArrayReader::new("stopDepartures").with_max_items(50),
+        body,
+        7,
+    )
+    .0
+}
+
+/// Every departure saved in `response`.
+pub fn departures(response: &str) -> Vec<Departure> {
+    stop_departures(response).unwrap()
+}
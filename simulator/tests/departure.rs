@@ -0,0 +1,58 @@
+mod common;
+
+use chrono::{DateTime, FixedOffset, TimeDelta};
+use common::{departures, COLLINS_AVENUE, KILLESTER};
+use matrix_simulator::departure::{Departure, Mode};
+
+fn at(time: &str) -> DateTime<FixedOffset> {
+    DateTime::parse_from_rfc3339(time).unwrap()
+}
+
+#[test]
+fn records_come_out_whole() {
+    let trains = departures(KILLESTER);
+    assert_eq!(trains.len(), 8);
+    assert_eq!(trains[1].service_number, "DART");
+    assert_eq!(trains[1].destination, "Bray (Daly)");
+    assert_eq!(trains[1].mode, Mode::Train);
+    assert_eq!(trains[1].operator.code, "IE");
+    assert_eq!(trains[1].operator.name, "Iarnród Éireann");
+    assert_eq!(trains[1].scheduled_departure, at("2024-10-07T07:04:00Z"));
+    assert_eq!(trains[1].leaves(), at("2024-10-07T07:06:00Z"));
+    assert!(!trains[1].cancelled);
+    assert!(trains[6].cancelled);
+
+    let buses = departures(COLLINS_AVENUE);
+    assert_eq!(buses[2].mode, Mode::Bus);
+    assert_eq!(buses[2].operator.name, "Go-Ahead Ireland");
+}
+
+#[test]
+fn trains_go_by_destination_and_buses_by_route() {
+    assert_eq!(departures(KILLESTER)[4].name(), "Dublin Connolly");
+    assert_eq!(departures(COLLINS_AVENUE)[3].name(), "27B");
+}
+
+#[test]
+fn delays_are_against_the_timetable() {
+    let buses = departures(COLLINS_AVENUE);
+    assert_eq!(buses[2].delay(), TimeDelta::minutes(2));
+    assert_eq!(buses[4].delay(), TimeDelta::minutes(-1));
+    // Untracked ones are taken to be on time
+    assert_eq!(buses[3].real_time_departure, None);
+    assert_eq!(buses[3].delay(), TimeDelta::zero());
+}
+
+#[test]
+fn missing_fields_fall_back_rather_than_fail() {
+    let departure: Departure = serde_json::from_str(
+        r#"{"scheduledDeparture": "2024-10-07T07:04:00.000Z", "transportMode": "FERRY"}"#,
+    )
+    .unwrap();
+    assert_eq!(departure.mode, Mode::Other);
+    assert_eq!(departure.service_number, "");
+    assert!(!departure.cancelled);
+
+    // Without a time there's nothing to show
+    assert!(serde_json::from_str::<Departure>(r#"{"serviceNumber": "27"}"#).is_err());
+}
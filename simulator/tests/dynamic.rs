@@ -0,0 +1,195 @@
+mod common;
+
+use common::{dyn_display, COLS, DEVICES, ROWS};
+use embedded_graphics::pixelcolor::{BinaryColor, Gray4};
+use embedded_graphics::prelude::{DrawTarget, OriginDimensions, Point, Size};
+use embedded_graphics::Pixel;
+use matrix_simulator::mock::RecordingSpi;
+use max7219::{
+    Corner, DecodeMode, DynFrameBuffer, DynGrayFrameBuffer, DynMax7219, DynTopology,
+    GrayFrameBuffer, Max7219, Module, Orientation, Rotation, Topology, Wiring,
+};
+
+#[test]
+fn init_blanks_every_device_in_one_transaction_per_register() {
+    let (mut display, spi) = dyn_display(DynTopology::new(
+        ROWS,
+        COLS,
+        Corner::BottomLeft,
+        Wiring::Progressive,
+    ));
+
+    display.init().unwrap();
+
+    // Display test, scan limit, decode mode, eight digits, intensity and shutdown
+    assert_eq!(spi.transactions().len(), 13);
+    for addr in 0..DEVICES {
+        let device = spi.device(addr);
+        assert!(!device.display_test);
+        assert_eq!(device.scan_limit, 0x07);
+        assert_eq!(device.intensity, 1);
+        assert_eq!(device.digits, [0; 8]);
+        assert!(device.shutdown);
+    }
+
+    // The digits are known to be blank, so an empty frame sends nothing
+    spi.reset_log();
+    display.present().unwrap();
+    assert!(spi.transactions().is_empty());
+}
+
+#[test]
+fn draws_like_the_const_sized_driver() {
+    for (start, wiring) in [
+        (Corner::BottomLeft, Wiring::Progressive),
+        (Corner::TopRight, Wiring::Serpentine),
+    ] {
+        let (mut dynamic, dynamic_spi) = dyn_display(DynTopology::new(ROWS, COLS, start, wiring));
+        let fixed_spi = RecordingSpi::new(DEVICES);
+        let mut fixed: Max7219<_, ROWS, COLS> =
+            Max7219::new(fixed_spi.clone(), Topology::new(start, wiring));
+
+        let pixels = [(0, 0), (9, 3), (23, 15), (12, 8)]
+            .map(|(x, y)| Pixel(Point::new(x, y), BinaryColor::On));
+        dynamic.draw_iter(pixels).unwrap();
+        fixed.draw_iter(pixels).unwrap();
+        dynamic.present().unwrap();
+        fixed.present().unwrap();
+
+        assert_eq!(dynamic_spi.transactions(), fixed_spi.transactions());
+    }
+}
+
+#[test]
+fn keeps_everything_the_const_sized_driver_has() {
+    let upside_down = Orientation {
+        rotation: Rotation::Clockwise180,
+        mirrored: false,
+    };
+    let mut orientations = [Orientation::default(); DEVICES];
+    orientations[4] = upside_down;
+    let (mut dynamic, dynamic_spi) = dyn_display(
+        DynTopology::new(ROWS, COLS, Corner::BottomLeft, Wiring::Progressive)
+            .with_orientations(&orientations),
+    );
+    let fixed_spi = RecordingSpi::new(DEVICES);
+    let topology = Topology::default().with_orientation(1, 1, upside_down);
+    let mut fixed: Max7219<_, ROWS, COLS> = Max7219::new(fixed_spi.clone(), topology);
+
+    // Per-module orientation, trims and 7-segment modules
+    let pixels = [(9, 9), (3, 3)].map(|(x, y)| Pixel(Point::new(x, y), BinaryColor::On));
+    dynamic.draw_iter(pixels).unwrap();
+    fixed.draw_iter(pixels).unwrap();
+    dynamic.set_trim(2, -1).unwrap();
+    fixed.set_trim(2, -1).unwrap();
+    let seven_segment = Module::SevenSegment(DecodeMode::CodeBAll);
+    dynamic.set_module(5, seven_segment).unwrap();
+    fixed.set_module(5, seven_segment).unwrap();
+    dynamic.write_str(5, "12.5");
+    fixed.write_str(5, "12.5");
+    dynamic.init().unwrap();
+    fixed.init().unwrap();
+    dynamic.present().unwrap();
+    fixed.present().unwrap();
+
+    // Grayscale
+    let mut levels = vec![[[0; 8]; 8]; DEVICES];
+    let mut dynamic_gray = DynGrayFrameBuffer::<Gray4, _>::new(dynamic.topology(), &mut levels);
+    let mut fixed_gray = GrayFrameBuffer::<Gray4, ROWS, COLS>::new(topology);
+    let pixels =
+        [(9, 9, 5), (0, 0, 15)].map(|(x, y, luma)| Pixel(Point::new(x, y), Gray4::new(luma)));
+    dynamic_gray.draw_iter(pixels).unwrap();
+    fixed_gray.draw_iter(pixels).unwrap();
+    for _ in 0..fixed_gray.subframes() {
+        dynamic.present_gray(&dynamic_gray).unwrap();
+        fixed.present_gray(&fixed_gray).unwrap();
+    }
+
+    assert_eq!(dynamic_spi.transactions(), fixed_spi.transactions());
+    assert_eq!(dynamic_spi.device(2).intensity, 0);
+    assert_eq!(dynamic_spi.device(5).digits[7], 0x01);
+}
+
+#[test]
+fn present_only_sends_what_changed() {
+    let (mut display, spi) = dyn_display(DynTopology::new(
+        ROWS,
+        COLS,
+        Corner::BottomLeft,
+        Wiring::Progressive,
+    ));
+    display
+        .draw_iter([Pixel(Point::new(0, 0), BinaryColor::On)])
+        .unwrap();
+    display.present().unwrap();
+
+    // The back buffer holds the blank frame shown before, redraw on it
+    display.clear(BinaryColor::Off).unwrap();
+    display
+        .draw_iter([Pixel(Point::new(0, 0), BinaryColor::On)])
+        .unwrap();
+    display
+        .draw_iter([Pixel(Point::new(1, 0), BinaryColor::On)])
+        .unwrap();
+
+    spi.reset_log();
+    display.present().unwrap();
+    assert_eq!(spi.writes_to(COLS), [(0x01, 0b1100_0000)]);
+}
+
+#[test]
+fn present_frame_shows_a_frame_drawn_on_another_thread() {
+    let topology = DynTopology::new(ROWS, COLS, Corner::BottomLeft, Wiring::Progressive);
+    let (mut display, spi) = dyn_display(topology);
+
+    display.clear(BinaryColor::On).unwrap();
+    display.present().unwrap();
+
+    let mut frame = std::thread::spawn(move || {
+        let mut frame = DynFrameBuffer::new(topology, vec![[0; 8]; DEVICES]);
+        frame
+            .draw_iter([Pixel(Point::new(9, 0), BinaryColor::On)])
+            .unwrap();
+        frame
+    })
+    .join()
+    .unwrap();
+    display.present_frame(&mut frame).unwrap();
+
+    assert_eq!(spi.device(COLS + 1).digits, [0x40, 0, 0, 0, 0, 0, 0, 0]);
+    assert_eq!(spi.device(0).digits, [0; 8]);
+
+    // The caller gets the previously shown frame back
+    assert_eq!(frame.pixel(Point::new(0, 0)), BinaryColor::On);
+    assert_eq!(frame.pixel(Point::new(23, 15)), BinaryColor::On);
+}
+
+#[test]
+fn any_grid_size_fits_one_firmware() {
+    let (mut clock, spi) =
+        dyn_display(DynTopology::new(1, 4, Corner::TopLeft, Wiring::Progressive));
+    assert_eq!(clock.size(), Size::new(32, 8));
+
+    clock
+        .draw_iter([Pixel(Point::new(31, 7), BinaryColor::On)])
+        .unwrap();
+    clock.present().unwrap();
+    assert_eq!(spi.device(3).digits[7], 0b0000_0001);
+}
+
+#[test]
+#[should_panic(expected = "needs a buffer")]
+fn short_buffers_are_rejected() {
+    let topology = DynTopology::new(ROWS, COLS, Corner::BottomLeft, Wiring::Progressive);
+    let mut buffer = [[0; 8]; 4];
+    DynMax7219::new(RecordingSpi::new(DEVICES), topology, &mut buffer[..]);
+}
+
+#[test]
+fn corner_and_wiring_parse_from_the_config() {
+    assert_eq!("top_right".parse(), Ok(Corner::TopRight));
+    assert_eq!("bottom_left".parse(), Ok(Corner::BottomLeft));
+    assert_eq!("serpentine".parse(), Ok(Wiring::Serpentine));
+    assert!("Bottom Left".parse::<Corner>().is_err());
+    assert!("zigzag".parse::<Wiring>().is_err());
+}
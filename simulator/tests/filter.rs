@@ -0,0 +1,96 @@
+mod common;
+
+use chrono::{DateTime, TimeDelta, TimeZone};
+use chrono_tz::{Europe::Dublin, Tz};
+use common::{departures, COLLINS_AVENUE, KILLESTER};
+use matrix_simulator::filter::Filter;
+use matrix_simulator::stops::{parse_stops, StopError};
+
+fn now() -> DateTime<Tz> {
+    Dublin.with_ymd_and_hms(2024, 10, 7, 8, 0, 0).unwrap()
+}
+
+/// The filter from a stop entry with `options`.
+fn filter(options: &str) -> Filter {
+    let stop = format!("8220IR3881 | Killester | TRAIN_STATION | KI | {options}");
+    parse_stops(&stop).unwrap().remove(0).filter
+}
+
+/// What the board calls each departure `filter` keeps from `response`: trains by
+/// destination, buses by route.
+fn kept(filter: &Filter, response: &str) -> Vec<String> {
+    filter
+        .apply(&departures(response), now())
+        .iter()
+        .map(|departure| departure.name().to_string())
+        .collect()
+}
+
+#[test]
+fn everything_gets_through_by_default_three_at_a_time() {
+    assert_eq!(
+        kept(&Filter::default(), COLLINS_AVENUE),
+        ["27", "N4", "104"]
+    );
+}
+
+#[test]
+fn only_the_trains_into_town_from_killester() {
+    let into_town = filter("to=Dublin Connolly, greystones,Bray (Daly)");
+    assert_eq!(
+        kept(&into_town, KILLESTER),
+        ["Bray (Daly)", "Greystones", "Dublin Connolly"]
+    );
+
+    // The same trains picked by the way they're heading
+    let southbound = filter("direction=Southbound");
+    assert_eq!(kept(&southbound, KILLESTER), kept(&into_town, KILLESTER));
+    let not_north = filter("not_direction=Northbound | max=2");
+    assert_eq!(kept(&not_north, KILLESTER), ["Bray (Daly)", "Greystones"]);
+}
+
+#[test]
+fn routes_and_operators_can_be_left_out() {
+    assert_eq!(
+        kept(&filter("not_route=N4"), COLLINS_AVENUE),
+        ["27", "104", "27B"]
+    );
+    assert_eq!(
+        kept(&filter("route=27,27B"), COLLINS_AVENUE),
+        ["27", "27B", "27"]
+    );
+    assert_eq!(
+        kept(&filter("operator=Go-Ahead Ireland"), COLLINS_AVENUE),
+        ["104", "104"]
+    );
+    assert_eq!(
+        kept(
+            &filter("not_operator=Dublin Bus | not_route=104"),
+            COLLINS_AVENUE
+        ),
+        Vec::<String>::new()
+    );
+}
+
+#[test]
+fn departures_too_soon_to_catch_are_hidden() {
+    let cutoff = filter("cutoff=6");
+    assert_eq!(cutoff.cutoff, TimeDelta::minutes(6));
+    // The 27 at 08:02 and the N4 at 08:05 are gone, the 104 is late enough to make
+    assert_eq!(kept(&cutoff, COLLINS_AVENUE), ["104", "27B", "27"]);
+    // Departures without real time information go by the timetable
+    assert_eq!(
+        kept(&filter("cutoff=10"), KILLESTER),
+        ["Greystones", "Dublin Connolly", "Howth"]
+    );
+}
+
+#[test]
+fn unknown_filter_options_are_turned_away() {
+    for option in ["colour=red", "max=4", "cutoff=soon", "not_max=1"] {
+        assert_eq!(
+            parse_stops(&format!("1 | One | BUS_STOP | A | {option}")),
+            Err(StopError::BadOption(option.to_string()))
+        );
+    }
+}
@@ -0,0 +1,213 @@
+use embedded_graphics::image::Image;
+use embedded_graphics::mock_display::MockDisplay;
+use embedded_graphics::mono_font::ascii::FONT_5X8;
+use embedded_graphics::mono_font::{MonoFont, MonoTextStyle};
+use embedded_graphics::pixelcolor::BinaryColor;
+use embedded_graphics::prelude::Point;
+use embedded_graphics::text::renderer::TextRenderer;
+use embedded_graphics::text::{Alignment, Baseline, Text, TextStyleBuilder};
+use embedded_graphics::Drawable;
+use matrix_fonts::{
+    ProportionalTextStyle, BUS, CLOCK, FIGURE_SPACE, FONT_3X5, FONT_4X7, FONT_DIGITS_16,
+    FONT_PROP_8, TRAIN, TRAM, WALK, WARNING,
+};
+
+const ICONS: [char; 6] = [BUS, TRAIN, TRAM, CLOCK, WARNING, WALK];
+
+fn characters() -> impl Iterator<Item = char> {
+    (' '..='~').chain(ICONS)
+}
+
+fn draw(font: &MonoFont<'_>, text: &str) -> MockDisplay<BinaryColor> {
+    let mut display = MockDisplay::new();
+    Text::with_baseline(
+        text,
+        Point::zero(),
+        MonoTextStyle::new(font, BinaryColor::On),
+        Baseline::Top,
+    )
+    .draw(&mut display)
+    .unwrap();
+    display
+}
+
+const PROPORTIONAL: ProportionalTextStyle<'static, BinaryColor> =
+    ProportionalTextStyle::new(&FONT_PROP_8, BinaryColor::On);
+
+fn proportional_width(style: ProportionalTextStyle<'_, BinaryColor>, text: &str) -> u32 {
+    style
+        .measure_string(text, Point::zero(), Baseline::Top)
+        .bounding_box
+        .size
+        .width
+}
+
+fn width(font: &MonoFont<'_>, text: &str) -> u32 {
+    MonoTextStyle::new(font, BinaryColor::On)
+        .measure_string(text, Point::zero(), Baseline::Top)
+        .bounding_box
+        .size
+        .width
+}
+
+#[test]
+fn glyphs_come_out_as_drawn_in_the_bdf() {
+    draw(&FONT_3X5, "A").assert_pattern(&[
+        " # ", //
+        "# #", //
+        "###", //
+        "# #", //
+        "# #", //
+    ]);
+    draw(&FONT_4X7, "g").assert_pattern(&[
+        "   ", //
+        "   ", //
+        " ##", //
+        "# #", //
+        " ##", //
+        "  #", //
+        "## ", //
+    ]);
+
+    let mut display = MockDisplay::new();
+    let glyph = FONT_PROP_8.glyph('T');
+    Image::new(&FONT_PROP_8.glyph_image(glyph), Point::zero())
+        .draw(&mut display)
+        .unwrap();
+    display.assert_pattern(&[
+        "###", //
+        ".#.", //
+        ".#.", //
+        ".#.", //
+        ".#.", //
+        ".#.", //
+        ".#.", //
+        "...", //
+    ]);
+}
+
+#[test]
+fn every_printable_character_and_icon_has_its_own_glyph() {
+    for font in [&FONT_3X5, &FONT_4X7] {
+        let mut indices: Vec<_> = characters().map(|c| font.glyph_mapping.index(c)).collect();
+        indices.dedup();
+        assert_eq!(indices.len(), characters().count());
+        assert_eq!(font.glyph_mapping.index('é'), font.glyph_mapping.index('?'));
+    }
+
+    for c in characters() {
+        assert_eq!(FONT_PROP_8.glyph(c).character, c);
+    }
+    assert_eq!(FONT_PROP_8.glyph('é'), FONT_PROP_8.glyph('?'));
+}
+
+#[test]
+fn icons_are_drawn_inside_a_character_cell() {
+    for icon in ICONS {
+        for font in [&FONT_3X5, &FONT_4X7] {
+            let display = draw(font, &icon.to_string());
+            let lit = display.affected_area();
+            assert!(!lit.is_zero_sized());
+            assert!(lit.size.width <= font.character_size.width);
+            assert!(lit.size.height <= font.character_size.height);
+        }
+        assert!(FONT_PROP_8.glyph(icon).width <= 7);
+    }
+}
+
+#[test]
+fn narrow_fonts_fit_more_on_a_row() {
+    let text = "Dublin Connolly";
+
+    assert_eq!(width(&FONT_5X8, text), 75);
+    assert_eq!(width(&FONT_4X7, text), 60);
+
+    assert!(proportional_width(PROPORTIONAL, text) < width(&FONT_5X8, text));
+    assert!(FONT_PROP_8.glyph('i').width < FONT_PROP_8.glyph('m').width);
+}
+
+#[test]
+fn proportional_text_draws_each_glyph_at_its_own_advance() {
+    let mut display = MockDisplay::new();
+    let end = Text::with_baseline("Ti|l", Point::zero(), PROPORTIONAL, Baseline::Top)
+        .draw(&mut display)
+        .unwrap();
+
+    display.assert_pattern(&[
+        "### # # #", //
+        " #    # #", //
+        " #  # # #", //
+        " #  # # #", //
+        " #  # # #", //
+        " #  # # #", //
+        " #  # # #", //
+        "      #  ", //
+    ]);
+    assert_eq!(end, Point::new(10, 0));
+    assert_eq!(proportional_width(PROPORTIONAL, "Ti|l"), 9);
+}
+
+#[test]
+fn kerning_tucks_pairs_together() {
+    let unkerned = PROPORTIONAL.with_kerning(false);
+
+    assert_eq!(FONT_PROP_8.kerning('T', 'o'), -1);
+    assert_eq!(FONT_PROP_8.kerning('o', 'T'), 0);
+    assert_eq!(
+        proportional_width(PROPORTIONAL, "To") + 1,
+        proportional_width(unkerned, "To")
+    );
+    assert_eq!(
+        proportional_width(PROPORTIONAL, "oT"),
+        proportional_width(unkerned, "oT")
+    );
+}
+
+#[test]
+fn tabular_digits_line_minutes_up_on_the_right() {
+    let tabular = PROPORTIONAL.with_tabular_digits(true);
+    let padded = format!("{FIGURE_SPACE}1m");
+
+    assert_ne!(
+        proportional_width(PROPORTIONAL, "1m"),
+        proportional_width(PROPORTIONAL, "0m")
+    );
+    assert_eq!(
+        proportional_width(tabular, "1m"),
+        proportional_width(tabular, "0m")
+    );
+    assert_eq!(
+        proportional_width(tabular, &padded),
+        proportional_width(tabular, "10m")
+    );
+
+    // Right aligned at the same point, the minute counts end in the same column
+    let right = TextStyleBuilder::new()
+        .alignment(Alignment::Right)
+        .baseline(Baseline::Top)
+        .build();
+    let edge = |text: &str| {
+        let mut display = MockDisplay::new();
+        Text::with_text_style(text, Point::new(30, 0), tabular, right)
+            .draw(&mut display)
+            .unwrap();
+        display.affected_area().bottom_right().unwrap().x
+    };
+    assert_eq!(edge("1m"), edge("10m"));
+    assert_eq!(edge("1m"), edge("59m"));
+}
+
+#[test]
+fn big_digits_are_two_rows_tall_and_all_as_wide() {
+    assert_eq!(FONT_DIGITS_16.height, 16);
+    for digit in '0'..='9' {
+        let glyph = FONT_DIGITS_16.glyph(digit);
+        assert_eq!(glyph.character, digit);
+        assert_eq!(glyph.advance, FONT_DIGITS_16.tabular_advance());
+    }
+    // So a blinking colon can be swapped for a space
+    assert_eq!(
+        FONT_DIGITS_16.glyph(':').advance,
+        FONT_DIGITS_16.glyph(' ').advance
+    );
+}
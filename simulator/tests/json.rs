@@ -0,0 +1,116 @@
+mod common;
+
+use common::{read, stop_departures, KILLESTER};
+use matrix_simulator::json::{ArrayReader, JsonError};
+use serde_json::Value;
+
+#[test]
+fn items_come_out_the_same_whatever_the_chunk_size() {
+    let whole: Value = serde_json::from_str(KILLESTER).unwrap();
+    let expected = &whole["stopDepartures"].as_array().unwrap()[..5];
+
+    for chunk in [1, 3, 64, 256] {
+        let reader = ArrayReader::new("stopDepartures").with_max_items(5);
+        let (items, _) = read::<Value>(reader, KILLESTER, chunk);
+        assert_eq!(items.unwrap(), expected);
+    }
+}
+
+#[test]
+fn reading_stops_once_there_are_enough_items() {
+    let (items, consumed) = read::<Value>(
+        ArrayReader::new("stopDepartures").with_max_items(2),
+        KILLESTER,
+        1,
+    );
+    assert_eq!(items.unwrap().len(), 2);
+    assert!(consumed < KILLESTER.len() / 3);
+
+    // Fewer in the body than asked for is fine
+    let (items, _) = read::<Value>(
+        ArrayReader::new("stopDepartures").with_max_items(50),
+        KILLESTER,
+        256,
+    );
+    assert_eq!(items.unwrap().len(), 8);
+}
+
+#[test]
+fn the_array_is_found_wherever_it_is_in_the_body() {
+    let body = r#"{
+        "errors": [],
+        "meta": {"stopDepartures": "not this one", "count": -1.5e+3, "ok": true},
+        "note": "a \"quoted\" é and a ] and a }",
+        "stopDepartures": [
+            {"serviceNumber": "27", "nested": {"stops": [1, 2, {"x": null}]}},
+            17,
+            {"serviceNumber": "27B"}
+        ],
+        "trailing": false
+    }"#;
+
+    let items = stop_departures::<Value>(body).unwrap();
+    let services: Vec<_> = items
+        .iter()
+        .map(|item| item["serviceNumber"].as_str().unwrap())
+        .collect();
+    assert_eq!(services, ["27", "27B"]);
+    assert_eq!(items[0]["nested"]["stops"][2]["x"], Value::Null);
+
+    assert_eq!(
+        stop_departures::<Value>(r#"{"stopDepartures": []}"#).unwrap(),
+        Vec::<Value>::new()
+    );
+}
+
+#[test]
+fn malformed_bodies_are_typed_errors() {
+    assert!(matches!(
+        stop_departures::<Value>(r#"{"stopDepartures": [{"serviceNumber": "27""#),
+        Err(JsonError::Truncated)
+    ));
+    assert!(matches!(
+        stop_departures::<Value>(r#"{"stopDepartures": [{"serviceNumber": 27,}]}"#),
+        Err(JsonError::Unexpected {
+            byte: b'}',
+            offset: 41
+        })
+    ));
+    assert!(matches!(
+        stop_departures::<Value>(r#"{"count": 01}"#),
+        Err(JsonError::Unexpected { byte: b'1', .. })
+    ));
+    assert!(matches!(
+        stop_departures::<Value>(r#"{"note": "tab	inside"}"#),
+        Err(JsonError::Unexpected { byte: b'\t', .. })
+    ));
+    assert!(matches!(
+        stop_departures::<Value>(r#"[{"stopDepartures": []}]"#),
+        Err(JsonError::Unexpected {
+            byte: b'[',
+            offset: 0
+        })
+    ));
+    assert!(matches!(
+        stop_departures::<Value>(r#"{"departures": []}"#),
+        Err(JsonError::MissingArray)
+    ));
+    assert!(matches!(
+        stop_departures::<Value>(r#"{"stopDepartures": {"27": "soon"}}"#),
+        Err(JsonError::MissingArray)
+    ));
+    assert!(matches!(
+        stop_departures::<Value>(&format!(r#"{{"stopDepartures": [{}]}}"#, "[".repeat(20))),
+        Err(JsonError::TooDeep)
+    ));
+}
+
+#[test]
+fn memory_is_bounded() {
+    let reader = ArrayReader::new("stopDepartures").with_max_item_len(100);
+    let (items, _) = read::<Value>(reader, KILLESTER, 256);
+    assert!(matches!(items, Err(JsonError::ItemTooLarge(100))));
+
+    let failing = ArrayReader::new("stopDepartures").read::<Value, _>(|_| Err("connection reset"));
+    assert!(matches!(failing, Err(JsonError::Read("connection reset"))));
+}
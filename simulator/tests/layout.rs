@@ -0,0 +1,174 @@
+mod common;
+
+use chrono::{TimeDelta, TimeZone};
+use chrono_tz::Europe::Dublin;
+use common::{bus, lit, train, wall};
+use embedded_graphics::pixelcolor::BinaryColor;
+use embedded_graphics::prelude::{Dimensions, DrawTarget, Point, Size};
+use embedded_graphics::primitives::Rectangle;
+use embedded_graphics::text::Alignment;
+use matrix_fonts::{FIGURE_SPACE, WALK};
+use matrix_simulator::board;
+use matrix_simulator::layout::{Layout, Padding, Zone};
+use std::time::Duration;
+
+#[test]
+fn padding_shrinks_the_content_and_alignment_places_it() {
+    let zone = Zone::new(Rectangle::new(Point::new(10, 8), Size::new(30, 8)))
+        .with_padding(Padding::new(1, 2, 0, 4));
+
+    assert_eq!(
+        zone.content(),
+        Rectangle::new(Point::new(14, 9), Size::new(24, 7))
+    );
+    assert_eq!(zone.origin(10), Point::new(14, 9));
+    assert_eq!(
+        zone.with_alignment(Alignment::Center).origin(10),
+        Point::new(21, 9)
+    );
+    assert_eq!(
+        zone.with_alignment(Alignment::Right).origin(10),
+        Point::new(28, 9)
+    );
+    // Too wide to align, it starts at the left and gets clipped
+    assert_eq!(
+        zone.with_alignment(Alignment::Right).origin(40),
+        Point::new(14, 9)
+    );
+}
+
+#[test]
+fn zones_are_looked_up_by_name() {
+    let first = Zone::new(Rectangle::new(Point::zero(), Size::new(8, 8)));
+    let second = Zone::new(Rectangle::new(Point::new(8, 0), Size::new(8, 8)));
+    let layout = Layout::new()
+        .with_zone("a", first)
+        .with_zone("b", first)
+        .with_zone("b", second);
+
+    assert_eq!(layout.zone("a"), Some(&first));
+    assert_eq!(layout.zone("b"), Some(&second));
+    assert_eq!(layout.zone("c"), None);
+    assert_eq!(layout.zones().count(), 2);
+}
+
+#[test]
+fn board_zones_never_overlap() {
+    for height in [24, 32] {
+        let bounds = Rectangle::new(Point::zero(), Size::new(120, height));
+        let layout = board::layout(bounds, 3);
+        let zones: Vec<_> = layout.zones().collect();
+
+        for (i, (name, zone)) in zones.iter().enumerate() {
+            assert_eq!(zone.area().intersection(&bounds), zone.area(), "{name}");
+            for (other, other_zone) in &zones[i + 1..] {
+                assert!(
+                    zone.area().intersection(&other_zone.area()).is_zero_sized(),
+                    "{name} overlaps {other}"
+                );
+            }
+        }
+    }
+}
+
+#[test]
+fn the_clock_sits_top_right_beside_the_first_row() {
+    let layout = board::layout(Rectangle::new(Point::zero(), Size::new(120, 24)), 3);
+
+    let clock = layout.zone(board::CLOCK).unwrap().area();
+    assert_eq!(clock.bottom_right(), Some(Point::new(119, 7)));
+
+    let first = layout.zone(&board::row_zone(0)).unwrap();
+    assert!(first.content().bottom_right().unwrap().x < clock.top_left.x - 1);
+    assert_eq!(
+        layout.zone(&board::row_zone(2)).unwrap().area().size.width,
+        120
+    );
+    assert!(layout.zone(&board::row_zone(3)).is_none());
+
+    // No line to spare on a three row panel
+    assert!(layout.zone(board::STATUS).is_none());
+    let taller = board::layout(Rectangle::new(Point::zero(), Size::new(120, 32)), 3);
+    assert_eq!(
+        taller.zone(board::STATUS).unwrap().area(),
+        Rectangle::new(Point::new(0, 24), Size::new(120, 8))
+    );
+}
+
+#[test]
+fn widgets_stay_inside_their_zones() {
+    let mut frame = wall();
+    let layout = board::layout(frame.bounding_box(), 3);
+    let time = Dublin.with_ymd_and_hms(2024, 6, 21, 13, 45, 0).unwrap();
+    let departures = [
+        train("Dublin Connolly", time),
+        train("Bray (Daly)", time),
+        train("Greystones", time),
+    ];
+
+    let clock = layout.zone(board::CLOCK).unwrap();
+    board::draw_clock(&mut frame, clock, time, board::HourFormat::Twelve).unwrap();
+    assert!(lit(&frame)
+        .iter()
+        .all(|point| clock.area().contains(*point)));
+    assert!(!lit(&frame).is_empty());
+
+    frame.clear(BinaryColor::Off).unwrap();
+    let row = layout.zone(&board::row_zone(0)).unwrap();
+    board::draw_scrolling_departures(
+        &mut frame,
+        "KI",
+        &board::format_departure_names(&departures, TimeDelta::zero(), time),
+        row,
+        Duration::from_secs(3),
+        20,
+    )
+    .unwrap();
+    assert!(lit(&frame)
+        .iter()
+        .all(|point| row.content().contains(*point)));
+}
+
+#[test]
+fn departures_leaving_in_the_walk_time_say_to_leave_now() {
+    let now = Dublin.with_ymd_and_hms(2024, 6, 21, 8, 0, 0).unwrap();
+    let soon = |minutes: i64| now + TimeDelta::seconds(minutes * 60 + 30);
+    let departures = [bus("27", soon(4)), bus("27B", soon(9)), bus("N4", soon(12))];
+
+    assert_eq!(
+        board::format_departure_names(&departures, TimeDelta::zero(), now),
+        format!("|27 {FIGURE_SPACE}4m|27B {FIGURE_SPACE}9m|N4 12m")
+    );
+    assert_eq!(
+        board::format_departure_names(&departures, TimeDelta::minutes(4), now),
+        format!("|{WALK}27 {FIGURE_SPACE}4m|27B {FIGURE_SPACE}9m|N4 12m")
+    );
+
+    // Half a minute on the 27 is too late to catch, and then it's time to go for the 27B
+    let later = now + TimeDelta::minutes(5);
+    assert_eq!(
+        board::format_departure_names(&departures, TimeDelta::minutes(4), later),
+        format!("|{WALK}27B {FIGURE_SPACE}4m|N4 {FIGURE_SPACE}7m")
+    );
+}
+
+#[test]
+fn late_and_cancelled_departures_say_so() {
+    let now = Dublin.with_ymd_and_hms(2024, 6, 21, 8, 0, 0).unwrap();
+    let soon = |minutes: i64| now + TimeDelta::seconds(minutes * 60 + 30);
+    let mut late = train("Greystones", soon(4));
+    if let Some(late) = late.as_mut() {
+        late.real_time_departure = Some(soon(7).fixed_offset());
+    }
+    let mut cancelled = train("Bray (Daly)", soon(9));
+    if let Some(cancelled) = cancelled.as_mut() {
+        cancelled.cancelled = true;
+    }
+    let departures = [late, cancelled, train("Howth", soon(12))];
+
+    // Late ones count down to when they're expected, with how late they are after
+    assert_eq!(
+        board::format_departure_names(&departures, TimeDelta::minutes(7), now),
+        format!("|{WALK}Greystones {FIGURE_SPACE}7m +3|Bray (Daly) cancelled|Howth 12m")
+    );
+}
@@ -0,0 +1,78 @@
+use embedded_graphics::mock_display::MockDisplay;
+use embedded_graphics::mono_font::ascii::FONT_5X8;
+use embedded_graphics::mono_font::MonoTextStyle;
+use embedded_graphics::pixelcolor::BinaryColor;
+use embedded_graphics::prelude::{Point, Size};
+use embedded_graphics::primitives::Rectangle;
+use matrix_simulator::marquee::Marquee;
+use std::time::Duration;
+
+const STYLE: MonoTextStyle<'static, BinaryColor> = MonoTextStyle::new(&FONT_5X8, BinaryColor::On);
+
+fn area() -> Rectangle {
+    Rectangle::new(Point::new(10, 8), Size::new(20, 8))
+}
+
+fn draw(
+    marquee: &Marquee<'_, MonoTextStyle<'static, BinaryColor>>,
+    elapsed: Duration,
+) -> MockDisplay<BinaryColor> {
+    let mut display = MockDisplay::new();
+    display.set_allow_overdraw(true);
+    marquee.draw(&mut display, elapsed).unwrap();
+    display
+}
+
+#[test]
+fn text_that_fits_stays_put() {
+    let marquee = Marquee::new("27B", STYLE, area());
+
+    assert_eq!(marquee.offset(Duration::from_secs(5)), 0);
+    assert_eq!(
+        draw(&marquee, Duration::from_secs(5)),
+        draw(&marquee, Duration::ZERO)
+    );
+}
+
+#[test]
+fn long_text_scrolls_at_the_configured_speed() {
+    let marquee = Marquee::new("Bray (Daly)", STYLE, area()).with_speed(10);
+
+    assert_eq!(marquee.offset(Duration::ZERO), 0);
+    assert_eq!(marquee.offset(Duration::from_millis(100)), 1);
+    assert_eq!(marquee.offset(Duration::from_millis(2500)), 25);
+
+    // Five pixels in, the second character sits where the first one started
+    let shifted = draw(&marquee, Duration::from_millis(500));
+    let mut second = MockDisplay::new();
+    second.set_allow_overdraw(true);
+    Marquee::new("ray (Daly)", STYLE, area())
+        .with_speed(0)
+        .draw(&mut second, Duration::ZERO)
+        .unwrap();
+    assert_eq!(shifted, second);
+}
+
+#[test]
+fn scrolling_wraps_round_after_the_gap() {
+    // 11 characters of 5 pixels, plus the gap
+    let marquee = Marquee::new("Bray (Daly)", STYLE, area())
+        .with_speed(10)
+        .with_gap(5);
+
+    assert_eq!(marquee.offset(Duration::from_secs(6)), 0);
+    assert_eq!(
+        draw(&marquee, Duration::from_secs(6)),
+        draw(&marquee, Duration::ZERO)
+    );
+}
+
+#[test]
+fn nothing_is_drawn_outside_the_area() {
+    let marquee = Marquee::new("Bray (Daly) 13m", STYLE, area());
+
+    for millis in (0..5000).step_by(250) {
+        let affected = draw(&marquee, Duration::from_millis(millis)).affected_area();
+        assert_eq!(affected.intersection(&area()), affected);
+    }
+}
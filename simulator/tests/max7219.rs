@@ -0,0 +1,604 @@
+mod common;
+
+use common::{display, display_with, COLS, DEVICES, ROWS};
+use embedded_graphics::pixelcolor::{BinaryColor, Gray2, Gray4, GrayColor};
+use embedded_graphics::prelude::{DrawTarget, Point};
+use embedded_graphics::Pixel;
+use matrix_simulator::chain::RegisterWrite;
+use max7219::{
+    code_b_for, intensity_for, segments_for, Command, Corner, DecodeMode, FixedBuffer, FrameBuffer,
+    GrayFrameBuffer, Module, Orientation, Rotation, Topology, Wiring, DECIMAL_POINT,
+};
+
+/// Lights a single pixel and returns the `(addr, digits)` of the only module that changed.
+fn light(topology: Topology<ROWS, COLS>, x: i32, y: i32) -> (usize, [u8; 8]) {
+    let (mut display, spi) = display_with(topology);
+
+    display
+        .draw_iter([Pixel(Point::new(x, y), BinaryColor::On)])
+        .unwrap();
+    display.present().unwrap();
+
+    let mut lit = (0..DEVICES).filter(|addr| spi.device(*addr).digits != [0; 8]);
+    let addr = lit.next().unwrap();
+    assert_eq!(lit.next(), None);
+    (addr, spi.device(addr).digits)
+}
+
+#[test]
+fn init_configures_every_device_and_leaves_them_shut_down() {
+    let (mut display, spi) = display();
+
+    display.init().unwrap();
+
+    for addr in 0..DEVICES {
+        let device = spi.device(addr);
+        assert!(!device.display_test);
+        assert_eq!(device.scan_limit, 0x07);
+        assert_eq!(device.intensity, 1);
+        assert_eq!(device.decode_mode, 0x00);
+        assert_eq!(device.digits, [0; 8]);
+        assert!(device.shutdown);
+    }
+}
+
+#[test]
+fn every_transaction_covers_the_whole_chain() {
+    let (mut display, spi) = display();
+
+    display.init().unwrap();
+    display.power_on().unwrap();
+    display.clear(BinaryColor::On).unwrap();
+    display.present().unwrap();
+
+    let transactions = spi.transactions();
+    // init: test, scan limit, decode mode, 8 digits, intensity and power off, each for
+    // every device at once; power on; then a digit of every device per frame
+    assert_eq!(transactions.len(), 13 + 1 + 8);
+    assert!(transactions.iter().all(|frame| frame.len() == DEVICES * 2));
+}
+
+#[test]
+fn power_on_wakes_every_device() {
+    let (mut display, spi) = display();
+
+    display.init().unwrap();
+    spi.reset_log();
+    display.power_on().unwrap();
+
+    for addr in 0..DEVICES {
+        assert!(!spi.device(addr).shutdown);
+        assert_eq!(spi.writes_to(addr), [(Command::Power as u8, 0x01)]);
+    }
+}
+
+#[test]
+fn single_device_writes_pad_the_rest_of_the_chain_with_noops() {
+    let (mut display, spi) = display();
+
+    display.set_intensity(4, 0x09).unwrap();
+
+    let mut expected = [0u8; DEVICES * 2];
+    expected[8] = Command::Intensity as u8;
+    expected[9] = 0x09;
+    assert_eq!(spi.transactions(), [expected.to_vec()]);
+    assert_eq!(
+        spi.writes(),
+        [RegisterWrite {
+            addr: 4,
+            register: Command::Intensity as u8,
+            data: 0x09,
+        }]
+    );
+    assert_eq!(spi.device(3).intensity, 0);
+    assert_eq!(spi.device(4).intensity, 0x09);
+}
+
+#[test]
+fn flush_sends_each_digit_to_every_device() {
+    let (mut display, spi) = display();
+
+    display.flush().unwrap();
+
+    let transactions = spi.transactions();
+    assert_eq!(transactions.len(), 8);
+    for (digit, frame) in transactions.iter().enumerate() {
+        for pair in frame.chunks_exact(2) {
+            assert_eq!(pair, [digit as u8 + 1, 0x00]);
+        }
+    }
+}
+
+#[test]
+fn flush_maps_the_top_left_pixel_to_the_start_of_the_bottom_row() {
+    let (mut display, spi) = display();
+
+    display
+        .draw_iter([
+            Pixel(Point::new(0, 0), BinaryColor::On),
+            Pixel(
+                Point::new(COLS as i32 * 8 - 1, ROWS as i32 * 8 - 1),
+                BinaryColor::On,
+            ),
+        ])
+        .unwrap();
+    display.present().unwrap();
+
+    // The chain starts on the bottom row, so the top left module comes after it
+    assert_eq!(spi.device(COLS).digits, [0x80, 0, 0, 0, 0, 0, 0, 0]);
+    assert_eq!(spi.device(COLS - 1).digits, [0, 0, 0, 0, 0, 0, 0, 0x01]);
+    for addr in (0..DEVICES).filter(|addr| *addr != COLS && *addr != COLS - 1) {
+        assert_eq!(spi.device(addr).digits, [0; 8]);
+    }
+}
+
+#[test]
+fn clear_display_only_blanks_the_addressed_device() {
+    let (mut display, spi) = display();
+
+    display.clear(BinaryColor::On).unwrap();
+    display.present().unwrap();
+    spi.reset_log();
+    display.clear_display(2).unwrap();
+
+    assert_eq!(spi.transactions().len(), 8);
+    assert_eq!(
+        spi.writes_to(2),
+        (1..=8).map(|digit| (digit, 0x00)).collect::<Vec<_>>()
+    );
+    assert_eq!(spi.device(2).digits, [0; 8]);
+    assert_eq!(spi.device(1).digits, [0xFF; 8]);
+    assert_eq!(spi.device(3).digits, [0xFF; 8]);
+}
+
+#[test]
+fn display_test_overrides_shutdown() {
+    let (mut display, spi) = display();
+
+    display.test(0, true).unwrap();
+    assert!(spi.device(0).display_test);
+    assert_eq!(spi.device(0).lit(3), 0xFF);
+
+    display.test(0, false).unwrap();
+    assert_eq!(spi.device(0).lit(3), 0x00);
+}
+
+#[test]
+fn serpentine_wiring_reverses_every_other_row() {
+    let topology = Topology::new(Corner::TopLeft, Wiring::Serpentine);
+
+    // Top row runs left to right, the second row comes back right to left
+    assert_eq!(light(topology, 0, 0).0, 0);
+    assert_eq!(light(topology, 23, 0).0, 2);
+    assert_eq!(light(topology, 23, 8).0, 3);
+    assert_eq!(light(topology, 0, 8).0, 5);
+}
+
+#[test]
+fn chain_can_start_from_any_corner() {
+    let top_right = Topology::new(Corner::TopRight, Wiring::Progressive);
+    assert_eq!(light(top_right, 23, 0).0, 0);
+    assert_eq!(light(top_right, 0, 8).0, 5);
+
+    let bottom_right = Topology::new(Corner::BottomRight, Wiring::Serpentine);
+    assert_eq!(light(bottom_right, 23, 8).0, 0);
+    assert_eq!(light(bottom_right, 0, 8).0, 2);
+    assert_eq!(light(bottom_right, 0, 0).0, 3);
+}
+
+#[test]
+fn rotated_and_mirrored_modules_are_drawn_upright() {
+    let rotated = |rotation, mirrored| {
+        Topology::default().with_orientation(0, 0, Orientation { rotation, mirrored })
+    };
+
+    // Pixel (1, 0) of the top left module, which is chain address 3
+    let mut expected = [0; 8];
+    expected[0] = 0x40;
+    assert_eq!(light(rotated(Rotation::None, false), 1, 0), (3, expected));
+
+    let mut expected = [0; 8];
+    expected[6] = 0x80;
+    assert_eq!(
+        light(rotated(Rotation::Clockwise90, false), 1, 0),
+        (3, expected)
+    );
+
+    let mut expected = [0; 8];
+    expected[7] = 0x02;
+    assert_eq!(
+        light(rotated(Rotation::Clockwise180, false), 1, 0),
+        (3, expected)
+    );
+
+    let mut expected = [0; 8];
+    expected[1] = 0x01;
+    assert_eq!(
+        light(rotated(Rotation::Clockwise270, false), 1, 0),
+        (3, expected)
+    );
+
+    let mut expected = [0; 8];
+    expected[0] = 0x02;
+    assert_eq!(light(rotated(Rotation::None, true), 1, 0), (3, expected));
+
+    // Other modules keep their own orientation
+    let mut expected = [0; 8];
+    expected[0] = 0x40;
+    assert_eq!(
+        light(rotated(Rotation::Clockwise90, true), 9, 0),
+        (4, expected)
+    );
+}
+
+#[test]
+fn flush_without_changes_sends_nothing() {
+    let (mut display, spi) = display();
+
+    display.flush().unwrap();
+    spi.reset_log();
+    display.flush().unwrap();
+
+    assert!(spi.transactions().is_empty());
+}
+
+#[test]
+fn flush_only_sends_changed_digits() {
+    let (mut display, spi) = display();
+
+    display.flush().unwrap();
+    spi.reset_log();
+    display
+        .draw_iter([Pixel(Point::new(2, 5), BinaryColor::On)])
+        .unwrap();
+    display.present().unwrap();
+
+    let mut expected = [0u8; DEVICES * 2];
+    expected[COLS * 2] = 6;
+    expected[COLS * 2 + 1] = 0x20;
+    assert_eq!(spi.transactions(), [expected.to_vec()]);
+    assert_eq!(spi.device(COLS).digits, [0, 0, 0, 0, 0, 0x20, 0, 0]);
+}
+
+#[test]
+fn changes_on_different_displays_share_frames() {
+    let (mut display, spi) = display();
+
+    display.flush().unwrap();
+    spi.reset_log();
+    display
+        .draw_iter([
+            Pixel(Point::new(0, 0), BinaryColor::On),
+            Pixel(Point::new(0, 1), BinaryColor::On),
+            Pixel(Point::new(8, 13), BinaryColor::On),
+        ])
+        .unwrap();
+    display.present().unwrap();
+
+    assert_eq!(spi.transactions().len(), 2);
+    assert_eq!(
+        spi.writes(),
+        [
+            RegisterWrite {
+                addr: 1,
+                register: 6,
+                data: 0x80,
+            },
+            RegisterWrite {
+                addr: COLS,
+                register: 1,
+                data: 0x80,
+            },
+            RegisterWrite {
+                addr: COLS,
+                register: 2,
+                data: 0x80,
+            },
+        ]
+    );
+}
+
+#[test]
+fn pixels_reverted_before_flush_are_not_sent() {
+    let (mut display, spi) = display();
+
+    display.flush().unwrap();
+    spi.reset_log();
+    display
+        .draw_iter([
+            Pixel(Point::new(4, 4), BinaryColor::On),
+            Pixel(Point::new(4, 4), BinaryColor::Off),
+        ])
+        .unwrap();
+    display.present().unwrap();
+
+    assert!(spi.transactions().is_empty());
+}
+
+#[test]
+fn invalidate_makes_the_next_flush_rewrite_everything() {
+    let (mut display, spi) = display();
+
+    display.flush().unwrap();
+    spi.reset_log();
+    display.invalidate();
+    display.flush().unwrap();
+
+    assert_eq!(spi.transactions().len(), 8);
+    assert_eq!(spi.writes().len(), DEVICES * 8);
+}
+
+#[test]
+fn flush_restores_a_cleared_display() {
+    let (mut display, spi) = display();
+
+    display.clear(BinaryColor::On).unwrap();
+    display.present().unwrap();
+    display.clear_display(2).unwrap();
+    spi.reset_log();
+    display.flush().unwrap();
+
+    assert_eq!(spi.transactions().len(), 8);
+    assert!(spi.writes().iter().all(|write| write.addr == 2));
+    assert_eq!(spi.device(2).digits, [0xFF; 8]);
+}
+
+#[test]
+fn drawing_stays_off_the_panel_until_present() {
+    let (mut display, spi) = display();
+
+    display.flush().unwrap();
+    spi.reset_log();
+    display.clear(BinaryColor::On).unwrap();
+    display.flush().unwrap();
+
+    assert!(spi.transactions().is_empty());
+
+    display.present().unwrap();
+
+    assert!((0..DEVICES).all(|addr| spi.device(addr).digits == [0xFF; 8]));
+}
+
+#[test]
+fn present_swaps_the_previous_frame_into_the_back_buffer() {
+    let (mut display, spi) = display();
+
+    display.clear(BinaryColor::On).unwrap();
+    display.present().unwrap();
+
+    // The back buffer now holds the blank frame from before
+    display
+        .draw_iter([Pixel(Point::new(0, 0), BinaryColor::On)])
+        .unwrap();
+    display.present().unwrap();
+
+    assert_eq!(spi.device(COLS).digits, [0x80, 0, 0, 0, 0, 0, 0, 0]);
+    assert!((0..DEVICES)
+        .filter(|addr| *addr != COLS)
+        .all(|addr| spi.device(addr).digits == [0; 8]));
+}
+
+#[test]
+fn present_frame_shows_a_frame_drawn_on_another_thread() {
+    let (mut display, spi) = display();
+
+    display.clear(BinaryColor::On).unwrap();
+    display.present().unwrap();
+
+    let frame = display.new_frame();
+    let mut frame = std::thread::spawn(move || {
+        let mut frame: FrameBuffer<ROWS, COLS> = frame;
+        frame
+            .draw_iter([Pixel(Point::new(9, 0), BinaryColor::On)])
+            .unwrap();
+        frame
+    })
+    .join()
+    .unwrap();
+    display.present_frame(&mut frame).unwrap();
+
+    assert_eq!(spi.device(COLS + 1).digits, [0x40, 0, 0, 0, 0, 0, 0, 0]);
+    assert_eq!(spi.device(0).digits, [0; 8]);
+
+    // The caller gets the previously shown frame back
+    let mut full = display.new_frame();
+    full.clear(BinaryColor::On).unwrap();
+    assert!(frame == full);
+}
+
+#[test]
+fn fixed_buffer_holds_the_state_and_one_spi_frame() {
+    // Six blocks of eight digits per device, and a [register, data] pair each
+    assert_eq!(
+        std::mem::size_of::<FixedBuffer<ROWS, COLS>>(),
+        DEVICES * (6 * 8 + 2)
+    );
+}
+
+#[test]
+fn brightness_spreads_the_percent_scale_over_all_intensity_steps() {
+    assert_eq!(intensity_for(0), 0);
+    assert_eq!(intensity_for(8), 0);
+    assert_eq!(intensity_for(9), 1);
+    assert_eq!(intensity_for(50), 5);
+    assert_eq!(intensity_for(100), 15);
+    assert_eq!(intensity_for(255), 15);
+    assert!((0..100).all(|percent| intensity_for(percent) <= intensity_for(percent + 1)));
+}
+
+#[test]
+fn brightness_steps_follow_a_gamma_curve_above_the_dim_end() {
+    // The lowest percentage each step is used at
+    let threshold = |step: u8| (0..=100).find(|percent| intensity_for(*percent) >= step);
+    for step in 1..16u8 {
+        let curve = 100.0 * ((step as f64 - 0.5) / 15.0).powf(1.0 / 1.7);
+        let threshold = threshold(step).unwrap() as f64;
+        match step {
+            1..=3 => assert!(threshold < curve, "step {step}"),
+            _ => assert!((threshold - curve).abs() <= 1.0, "step {step}"),
+        }
+    }
+}
+
+#[test]
+fn global_intensity_reaches_every_device_in_one_transaction() {
+    let (mut display, spi) = display();
+
+    display.set_brightness(100).unwrap();
+
+    assert_eq!(spi.transactions().len(), 1);
+    assert!((0..DEVICES).all(|addr| spi.device(addr).intensity == 15));
+}
+
+#[test]
+fn trims_offset_single_modules_from_the_global_intensity() {
+    let (mut display, spi) = display();
+
+    display.set_global_intensity(8).unwrap();
+    spi.reset_log();
+    display.set_trim(1, -3).unwrap();
+
+    assert_eq!(spi.writes_to(1), [(Command::Intensity as u8, 5)]);
+    assert_eq!(spi.writes().len(), 1);
+
+    // Trims stick around and are clamped to the chip's range
+    display.set_trim(4, 2).unwrap();
+    display.set_global_intensity(14).unwrap();
+
+    assert_eq!(spi.device(0).intensity, 14);
+    assert_eq!(spi.device(1).intensity, 11);
+    assert_eq!(spi.device(4).intensity, 15);
+}
+
+#[test]
+fn gray_pixels_are_lit_in_as_many_subframes_as_their_luma() {
+    let (mut display, spi) = display();
+    let mut gray = GrayFrameBuffer::<Gray4, ROWS, COLS>::new(Topology::default());
+    assert_eq!(gray.subframes(), 15);
+
+    let lumas = [0, 1, 5, 8, 15];
+    for (x, luma) in lumas.into_iter().enumerate() {
+        gray.draw_iter([Pixel(Point::new(x as i32, 0), Gray4::new(luma))])
+            .unwrap();
+    }
+
+    // The top left pixels sit on the top digit of the first module in the bottom row
+    let mut lit = [0u8; 5];
+    for _ in 0..gray.subframes() {
+        display.present_gray(&gray).unwrap();
+        let digit = spi.device(COLS).digits[0];
+        for (column, count) in lit.iter_mut().enumerate() {
+            *count += (digit >> (7 - column)) & 1;
+        }
+    }
+
+    assert_eq!(lit, lumas);
+}
+
+#[test]
+fn dim_gray_pixels_are_spread_over_the_cycle() {
+    let (mut display, spi) = display();
+    let mut gray = GrayFrameBuffer::<Gray2, ROWS, COLS>::new(Topology::default());
+    assert_eq!(gray.subframes(), 3);
+
+    gray.draw_iter([Pixel(Point::zero(), Gray2::new(1))])
+        .unwrap();
+
+    let cycle: Vec<u8> = (0..6)
+        .map(|_| {
+            display.present_gray(&gray).unwrap();
+            spi.device(COLS).digits[0] >> 7
+        })
+        .collect();
+
+    assert_eq!(cycle.iter().sum::<u8>(), 2);
+    assert_eq!(cycle[..3], cycle[3..]);
+}
+
+#[test]
+fn present_gray_only_sends_digits_that_change_between_subframes() {
+    let (mut display, spi) = display();
+    let mut gray = GrayFrameBuffer::<Gray2, ROWS, COLS>::new(Topology::default());
+    gray.draw_iter([Pixel(Point::zero(), Gray2::WHITE)])
+        .unwrap();
+    display.present_gray(&gray).unwrap();
+
+    // A fully lit pixel never changes, so later subframes have nothing to send
+    spi.reset_log();
+    display.present_gray(&gray).unwrap();
+    display.present_gray(&gray).unwrap();
+    assert!(spi.transactions().is_empty());
+}
+
+#[test]
+fn init_sets_each_module_to_its_decode_mode() {
+    let (mut display, spi) = display();
+    display
+        .set_module(1, Module::SevenSegment(DecodeMode::CodeBAll))
+        .unwrap();
+    display
+        .set_module(2, Module::SevenSegment(DecodeMode::NoDecode))
+        .unwrap();
+
+    display.init().unwrap();
+
+    assert_eq!(spi.device(0).decode_mode, 0x00);
+    assert_eq!(spi.device(1).decode_mode, 0xFF);
+    assert_eq!(spi.device(2).decode_mode, 0x00);
+}
+
+#[test]
+fn write_str_uses_code_b_on_decoded_digits_only() {
+    let (mut display, spi) = display();
+    display
+        .set_module(0, Module::SevenSegment(DecodeMode::CodeBDigits0To3))
+        .unwrap();
+
+    display.write_str(0, "HELP12.5");
+    display.flush().unwrap();
+
+    // Digit 7 is the leftmost, and the point folds into the 2
+    assert_eq!(
+        spi.device(0).digits,
+        [
+            code_b_for(' ').unwrap(),
+            code_b_for('5').unwrap(),
+            code_b_for('2').unwrap() | DECIMAL_POINT,
+            code_b_for('1').unwrap(),
+            segments_for('P'),
+            segments_for('L'),
+            segments_for('E'),
+            segments_for('H'),
+        ]
+    );
+}
+
+#[test]
+fn seven_segment_modules_share_the_chain_with_matrices() {
+    let (mut display, spi) = display();
+    display
+        .set_module(COLS, Module::SevenSegment(DecodeMode::CodeBAll))
+        .unwrap();
+
+    // The top left pixel lands on the 7-segment module and isn't shown, the next module is
+    display
+        .draw_iter([
+            Pixel(Point::new(0, 0), BinaryColor::On),
+            Pixel(Point::new(8, 0), BinaryColor::On),
+        ])
+        .unwrap();
+    display.set_digit(COLS, 0, code_b_for('7').unwrap());
+    display.present().unwrap();
+
+    assert_eq!(
+        spi.device(COLS).digits,
+        [7, 0x0F, 0x0F, 0x0F, 0x0F, 0x0F, 0x0F, 0x0F]
+    );
+    assert_eq!(spi.device(COLS + 1).digits[0], 0b1000_0000);
+
+    // Only the changed digit goes out on the next flush
+    spi.reset_log();
+    display.set_digit(COLS, 0, code_b_for('8').unwrap());
+    display.flush().unwrap();
+    assert_eq!(spi.writes_to(COLS), [(0x01, 8)]);
+}
@@ -0,0 +1,97 @@
+use chrono::{DateTime, Duration, NaiveDate, NaiveTime, TimeZone};
+use chrono_tz::{Europe::Dublin, Tz};
+use matrix_simulator::schedule::{sun_events, Dimming, DimmingSchedule, SunEvents};
+
+const DUBLIN: (f64, f64) = (53.3498, -6.2603);
+
+fn dublin(y: i32, m: u32, d: u32, h: u32, min: u32) -> DateTime<Tz> {
+    Dublin.with_ymd_and_hms(y, m, d, h, min, 0).unwrap()
+}
+
+fn schedule() -> DimmingSchedule {
+    DimmingSchedule::new(DUBLIN.0, DUBLIN.1, 80, 0, Duration::minutes(60))
+}
+
+fn assert_close(actual: DateTime<Tz>, expected: DateTime<Tz>) {
+    let error = (actual - expected).num_seconds().abs();
+    assert!(error <= 180, "{actual} is {error}s away from {expected}");
+}
+
+#[test]
+fn dublin_midsummer() {
+    let date = NaiveDate::from_ymd_opt(2024, 6, 21).unwrap();
+    let SunEvents::Sun { rise, set } = sun_events(date, DUBLIN.0, DUBLIN.1) else {
+        panic!("the sun rises in Dublin");
+    };
+    assert_close(rise.with_timezone(&Dublin), dublin(2024, 6, 21, 4, 57));
+    assert_close(set.with_timezone(&Dublin), dublin(2024, 6, 21, 21, 57));
+}
+
+#[test]
+fn dublin_midwinter() {
+    let date = NaiveDate::from_ymd_opt(2024, 12, 21).unwrap();
+    let SunEvents::Sun { rise, set } = sun_events(date, DUBLIN.0, DUBLIN.1) else {
+        panic!("the sun rises in Dublin");
+    };
+    assert_close(rise.with_timezone(&Dublin), dublin(2024, 12, 21, 8, 38));
+    assert_close(set.with_timezone(&Dublin), dublin(2024, 12, 21, 16, 8));
+}
+
+#[test]
+fn polar_day_and_night() {
+    let midsummer = NaiveDate::from_ymd_opt(2024, 6, 21).unwrap();
+    let midwinter = NaiveDate::from_ymd_opt(2024, 12, 21).unwrap();
+    // Tromsø
+    assert_eq!(sun_events(midsummer, 69.65, 18.96), SunEvents::AlwaysUp);
+    assert_eq!(sun_events(midwinter, 69.65, 18.96), SunEvents::AlwaysDown);
+}
+
+#[test]
+fn day_and_night_brightness() {
+    let schedule = schedule();
+    assert_eq!(schedule.at(dublin(2024, 6, 21, 13, 0)), Dimming::On(80));
+    assert_eq!(schedule.at(dublin(2024, 6, 21, 23, 30)), Dimming::On(0));
+    assert_eq!(schedule.at(dublin(2024, 6, 21, 2, 0)), Dimming::On(0));
+}
+
+#[test]
+fn ramps_around_sunrise_and_sunset() {
+    let date = NaiveDate::from_ymd_opt(2024, 6, 21).unwrap();
+    let SunEvents::Sun { rise, set } = sun_events(date, DUBLIN.0, DUBLIN.1) else {
+        panic!("the sun rises in Dublin");
+    };
+    let schedule = schedule();
+
+    assert_eq!(schedule.at(rise.with_timezone(&Dublin)), Dimming::On(40));
+    assert_eq!(schedule.at(set.with_timezone(&Dublin)), Dimming::On(40));
+
+    // Half way into the second half of the ramp
+    let later = (rise + Duration::minutes(15)).with_timezone(&Dublin);
+    assert_eq!(schedule.at(later), Dimming::On(60));
+
+    let before = (rise - Duration::minutes(31)).with_timezone(&Dublin);
+    let after = (rise + Duration::minutes(31)).with_timezone(&Dublin);
+    assert_eq!(schedule.at(before), Dimming::On(0));
+    assert_eq!(schedule.at(after), Dimming::On(80));
+}
+
+#[test]
+fn off_window_spanning_midnight() {
+    let schedule = schedule().with_off_window(
+        NaiveTime::from_hms_opt(1, 0, 0).unwrap(),
+        NaiveTime::from_hms_opt(6, 0, 0).unwrap(),
+    );
+    assert_eq!(schedule.at(dublin(2024, 6, 21, 0, 59)), Dimming::On(0));
+    assert_eq!(schedule.at(dublin(2024, 6, 21, 1, 0)), Dimming::Off);
+    assert_eq!(schedule.at(dublin(2024, 6, 21, 5, 59)), Dimming::Off);
+    assert_ne!(schedule.at(dublin(2024, 6, 21, 6, 0)), Dimming::Off);
+
+    let schedule = schedule.with_off_window(
+        NaiveTime::from_hms_opt(23, 30, 0).unwrap(),
+        NaiveTime::from_hms_opt(6, 0, 0).unwrap(),
+    );
+    assert_eq!(schedule.at(dublin(2024, 6, 21, 23, 0)), Dimming::On(0));
+    assert_eq!(schedule.at(dublin(2024, 6, 21, 23, 45)), Dimming::Off);
+    assert_eq!(schedule.at(dublin(2024, 6, 22, 3, 0)), Dimming::Off);
+    assert_eq!(schedule.at(dublin(2024, 6, 22, 12, 0)), Dimming::On(80));
+}
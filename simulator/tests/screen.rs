@@ -0,0 +1,307 @@
+mod common;
+
+use chrono::{DateTime, TimeZone};
+use chrono_tz::{Asia::Tokyo, Europe::Dublin, Tz};
+use common::{bus, lit, wall, Wall};
+use embedded_graphics::pixelcolor::BinaryColor;
+use embedded_graphics::prelude::{Dimensions, Point, PointsIter, Size};
+use embedded_graphics::primitives::Rectangle;
+use matrix_simulator::board::{self, HourFormat};
+use matrix_simulator::screen::{
+    parse_rotation, ClockScreen, DeparturesScreen, MessageScreen, Rotation, RotationError, Screen,
+    ScreenKind, Slot, Weather, WeatherScreen,
+};
+use max7219::{FrameBuffer, Topology};
+use std::cell::Cell;
+use std::rc::Rc;
+use std::time::Duration;
+
+fn at(h: u32, min: u32, s: u32) -> DateTime<Tz> {
+    Dublin.with_ymd_and_hms(2024, 10, 1, h, min, s).unwrap()
+}
+
+fn seconds(seconds: u64) -> Duration {
+    Duration::from_secs(seconds)
+}
+
+/// A screen that asks to stay up for a fixed time and draws nothing.
+struct Blank(Duration);
+
+impl Screen<Wall> for Blank {
+    fn dwell(&self) -> Duration {
+        self.0
+    }
+
+    fn draw(
+        &self,
+        _: &mut Wall,
+        _: DateTime<Tz>,
+        _: Duration,
+    ) -> Result<(), core::convert::Infallible> {
+        Ok(())
+    }
+}
+
+#[test]
+fn the_screens_list_sets_the_order_and_how_long_each_stays() {
+    assert_eq!(
+        parse_rotation("departures:30, clock:10,message").unwrap(),
+        [
+            Slot {
+                kind: ScreenKind::Departures,
+                dwell: Some(seconds(30)),
+            },
+            Slot {
+                kind: ScreenKind::Clock,
+                dwell: Some(seconds(10)),
+            },
+            Slot {
+                kind: ScreenKind::Message,
+                dwell: None,
+            },
+        ]
+    );
+
+    assert_eq!(
+        parse_rotation("clock, radar"),
+        Err(RotationError::UnknownScreen("radar".to_string()))
+    );
+    assert_eq!(
+        parse_rotation("clock:0"),
+        Err(RotationError::BadDwell("0".to_string()))
+    );
+    assert_eq!(parse_rotation(" , "), Err(RotationError::NoScreens));
+}
+
+#[test]
+fn each_screen_stays_up_for_its_dwell_then_the_next_comes_round() {
+    let mut rotation = Rotation::<Wall>::new()
+        .with_screen(Blank(seconds(30)), None)
+        .with_screen(Blank(seconds(30)), Some(seconds(5)));
+
+    assert!(!rotation.advance(seconds(29)));
+    assert_eq!(rotation.index(), 0);
+    assert!(rotation.advance(seconds(30)));
+    assert_eq!(rotation.index(), 1);
+    assert_eq!(rotation.elapsed(seconds(32)), seconds(2));
+
+    // The config's 5 seconds win over the 30 the screen asks for
+    assert!(!rotation.advance(seconds(34)));
+    assert!(rotation.advance(seconds(35)));
+    assert_eq!(rotation.index(), 0);
+}
+
+#[test]
+fn a_lone_screen_never_moves_on() {
+    let mut rotation = Rotation::<Wall>::new().with_screen(Blank(seconds(1)), None);
+
+    assert!(!rotation.advance(seconds(60)));
+    assert_eq!(rotation.elapsed(seconds(60)), seconds(60));
+    assert!(Rotation::<Wall>::new().current().is_none());
+}
+
+#[test]
+fn departures_are_fetched_every_20_seconds_while_up() {
+    let fetches = Rc::new(Cell::new(0));
+    let counter = fetches.clone();
+    let mut screen = DeparturesScreen::new(board::layout(wall().bounding_box(), 3), move |now| {
+        counter.set(counter.get() + 1);
+        let departures = [bus("27", now), None, None];
+        Some(vec![("CA".to_string(), departures)])
+    });
+
+    assert!(Screen::<Wall>::update(&mut screen, at(9, 0, 0)));
+    assert!(!Screen::<Wall>::update(&mut screen, at(9, 0, 19)));
+    assert!(Screen::<Wall>::update(&mut screen, at(9, 0, 20)));
+    assert_eq!(fetches.get(), 2);
+
+    let mut display = wall();
+    screen
+        .draw(&mut display, at(9, 0, 20), Duration::ZERO)
+        .unwrap();
+    assert!(!lit(&display).is_empty());
+}
+
+#[test]
+fn failed_fetches_keep_the_last_departures_and_raise_the_status_bar() {
+    let fail = Rc::new(Cell::new(false));
+    let failing = fail.clone();
+    let mut screen = DeparturesScreen::new(board::layout(wall().bounding_box(), 2), move |now| {
+        let departures = [bus("27", now), None, None];
+        (!failing.get()).then(|| vec![("CA".to_string(), departures)])
+    });
+    let status = board::layout(wall().bounding_box(), 2)
+        .zone(board::STATUS)
+        .unwrap()
+        .area();
+    let draw = |screen: &DeparturesScreen<_>| {
+        let mut display = wall();
+        screen
+            .draw(&mut display, at(9, 0, 0), Duration::ZERO)
+            .unwrap();
+        let row = Rectangle::new(Point::zero(), Size::new(20, 8));
+        let count = |area: Rectangle| {
+            area.points()
+                .filter(|point| display.pixel(*point) == BinaryColor::On)
+                .count()
+        };
+        (count(row), count(status))
+    };
+
+    Screen::<Wall>::update(&mut screen, at(9, 0, 0));
+    let (row, bar) = draw(&screen);
+    assert!(row > 0);
+    assert_eq!(bar, 0);
+
+    fail.set(true);
+    Screen::<Wall>::update(&mut screen, at(9, 0, 20));
+    let (still, bar) = draw(&screen);
+    assert_eq!(still, row);
+    assert!(bar > 0);
+}
+
+#[test]
+fn failed_weather_fetches_wait_as_long_as_good_ones_to_be_tried_again() {
+    let fetches = Rc::new(Cell::new(0));
+    let counter = fetches.clone();
+    let mut screen = WeatherScreen::new(move || {
+        counter.set(counter.get() + 1);
+        // Only the first fetch gets through
+        (counter.get() == 1).then_some(Weather {
+            temperature: 11.4,
+            code: 61,
+        })
+    });
+    let draw = |screen: &WeatherScreen<_>| {
+        let mut display = wall();
+        screen
+            .draw(&mut display, at(9, 0, 0), Duration::ZERO)
+            .unwrap();
+        lit(&display)
+    };
+
+    assert!(Screen::<Wall>::update(&mut screen, at(9, 0, 0)));
+    let shown = draw(&screen);
+
+    // Not asked again every frame after failing, and the last weather stays up
+    assert!(!Screen::<Wall>::update(&mut screen, at(9, 15, 0)));
+    for second in 1..60 {
+        Screen::<Wall>::update(&mut screen, at(9, 15, second));
+    }
+    assert_eq!(fetches.get(), 2);
+    assert_eq!(draw(&screen), shown);
+
+    Screen::<Wall>::update(&mut screen, at(9, 30, 0));
+    assert_eq!(fetches.get(), 3);
+}
+
+#[test]
+fn long_messages_stay_up_until_they_have_scrolled_round() {
+    let bounds = wall().bounding_box();
+    let short: &dyn Screen<Wall> = &MessageScreen::new("Hello", bounds);
+    assert_eq!(short.dwell(), seconds(10));
+
+    let text = "Services are diverted via Amiens Street all weekend while the bridge is repaired";
+    let long: &dyn Screen<Wall> = &MessageScreen::new(text, bounds).with_speed(20);
+    assert!(long.dwell() > seconds(10));
+
+    let mut display = wall();
+    long.draw(&mut display, at(9, 0, 0), Duration::ZERO)
+        .unwrap();
+    assert!(!lit(&display).is_empty());
+}
+
+fn clock(screen: ClockScreen, now: DateTime<Tz>) -> Wall {
+    let mut display = wall();
+    screen.draw(&mut display, now, Duration::ZERO).unwrap();
+    display
+}
+
+#[test]
+fn twelve_hour_clocks_show_noon_and_midnight_as_12() {
+    assert_eq!(HourFormat::Twelve.hour(&at(12, 0, 0)), 12);
+    assert_eq!(HourFormat::Twelve.hour(&at(0, 30, 0)), 12);
+    assert_eq!(HourFormat::Twelve.hour(&at(13, 5, 0)), 1);
+    assert_eq!(HourFormat::TwentyFour.hour(&at(0, 30, 0)), 0);
+
+    // Midnight and noon only differ by the AM or PM at the top of the digits
+    let twelve = ClockScreen::new(Dublin).with_hour_format(HourFormat::Twelve);
+    let (midnight, noon) = (clock(twelve, at(0, 5, 0)), clock(twelve, at(12, 5, 0)));
+    let different: Vec<_> = midnight
+        .bounding_box()
+        .points()
+        .filter(|point| midnight.pixel(*point) != noon.pixel(*point))
+        .collect();
+    assert!(!different.is_empty());
+    assert!(different.iter().all(|point| point.y < 5));
+}
+
+#[test]
+fn the_big_clock_fills_two_rows_with_the_date_under_it() {
+    let display = clock(ClockScreen::new(Dublin), at(9, 41, 0));
+    let rows = |top: i32, height: u32| {
+        Rectangle::new(Point::new(0, top), Size::new(120, height))
+            .points()
+            .filter(|point| display.pixel(*point) == BinaryColor::On)
+            .count()
+    };
+    assert!(rows(0, 16) > 0);
+    assert!(rows(16, 8) > 0);
+
+    let undated = clock(ClockScreen::new(Dublin).with_date(false), at(9, 41, 0));
+    assert!(lit(&undated).len() < lit(&display).len());
+}
+
+#[test]
+fn only_the_colon_blinks() {
+    let screen = ClockScreen::new(Dublin);
+    let lit_colon = clock(screen, at(9, 41, 0));
+    let dark_colon = clock(screen, at(9, 41, 0) + chrono::Duration::milliseconds(600));
+
+    let lit_only: Vec<_> = lit_colon
+        .bounding_box()
+        .points()
+        .filter(|point| lit_colon.pixel(*point) != dark_colon.pixel(*point))
+        .collect();
+    assert!(!lit_only.is_empty());
+    assert!(lit_only
+        .iter()
+        .all(|point| lit_colon.pixel(*point) == BinaryColor::On));
+    // All of it in one narrow column between the hours and the minutes
+    let left = lit_only.iter().map(|point| point.x).min().unwrap();
+    let right = lit_only.iter().map(|point| point.x).max().unwrap();
+    assert!(right - left < 4);
+
+    let steady = screen.with_blinking_colon(false);
+    assert_eq!(
+        lit(&clock(
+            steady,
+            at(9, 41, 0) + chrono::Duration::milliseconds(600)
+        )),
+        lit(&lit_colon)
+    );
+}
+
+#[test]
+fn the_clock_shows_its_own_timezone() {
+    // Tokyo is eight hours ahead of Irish summer time
+    assert_eq!(
+        lit(&clock(ClockScreen::new(Tokyo), at(9, 41, 0))),
+        lit(&clock(ClockScreen::new(Dublin), at(17, 41, 0)))
+    );
+}
+
+#[test]
+fn short_panels_get_the_time_in_ordinary_text() {
+    let mut desk: FrameBuffer<1, 4> = FrameBuffer::new(Topology::default());
+    let screen = ClockScreen::new(Dublin).with_hour_format(HourFormat::Twelve);
+    Screen::<FrameBuffer<1, 4>>::draw(&screen, &mut desk, at(21, 41, 0), Duration::ZERO).unwrap();
+
+    let lit: Vec<_> = desk
+        .bounding_box()
+        .points()
+        .filter(|point| desk.pixel(*point) == BinaryColor::On)
+        .collect();
+    assert!(!lit.is_empty());
+    assert!(lit.iter().all(|point| point.y < 8));
+}
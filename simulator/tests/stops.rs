@@ -0,0 +1,88 @@
+use chrono::TimeDelta;
+use matrix_simulator::filter::Filter;
+use matrix_simulator::stops::{parse_stops, Stop, StopError};
+
+fn stop(id: &str, name: &str, stop_type: &str, label: &str, row: usize) -> Stop {
+    Stop {
+        id: id.to_string(),
+        name: name.to_string(),
+        stop_type: stop_type.to_string(),
+        label: label.to_string(),
+        row,
+        walk: TimeDelta::zero(),
+        filter: Filter::default(),
+    }
+}
+
+#[test]
+fn stops_are_read_one_per_line_in_row_order() {
+    let stops = parse_stops(
+        "
+        8220DB000609 | Castle Grove, Clontarf | BUS_STOP | CG | 1
+        8220IR3881 | Killester | TRAIN_STATION | KI | 0
+        ",
+    )
+    .unwrap();
+
+    assert_eq!(
+        stops,
+        [
+            stop("8220IR3881", "Killester", "TRAIN_STATION", "KI", 0),
+            stop(
+                "8220DB000609",
+                "Castle Grove, Clontarf",
+                "BUS_STOP",
+                "CG",
+                1
+            ),
+        ]
+    );
+}
+
+#[test]
+fn stops_without_a_row_go_in_the_order_listed() {
+    let stops = parse_stops("1 | One | BUS_STOP | A; 2 | Two | BUS_STOP | B").unwrap();
+
+    let labels: Vec<_> = stops
+        .iter()
+        .map(|stop| (stop.label.as_str(), stop.row))
+        .collect();
+    assert_eq!(labels, [("A", 0), ("B", 1)]);
+}
+
+#[test]
+fn broken_stop_lists_are_turned_away() {
+    assert_eq!(
+        parse_stops("8220IR3881 | Killester | TRAIN_STATION"),
+        Err(StopError::MissingField(
+            "8220IR3881 | Killester | TRAIN_STATION".to_string()
+        ))
+    );
+    assert_eq!(
+        parse_stops("1 | One | BUS_STOP | A | 1"),
+        Err(StopError::BadRow("1".to_string()))
+    );
+    assert_eq!(
+        parse_stops("1 | One | BUS_STOP | A; 2 | Two | BUS_STOP | B | 0"),
+        Err(StopError::SameRow(0))
+    );
+    assert_eq!(parse_stops(" ; \n"), Err(StopError::NoStops));
+}
+
+#[test]
+fn walk_times_hide_departures_there_is_no_getting_to() {
+    let stops =
+        parse_stops("1 | One | BUS_STOP | A | walk=9; 2 | Two | BUS_STOP | B | walk=9 | cutoff=5")
+            .unwrap();
+
+    assert_eq!(stops[0].walk, TimeDelta::minutes(9));
+    assert_eq!(stops[0].filter.cutoff, TimeDelta::minutes(9));
+    // A cutoff of its own wins
+    assert_eq!(stops[1].walk, TimeDelta::minutes(9));
+    assert_eq!(stops[1].filter.cutoff, TimeDelta::minutes(5));
+
+    assert_eq!(
+        parse_stops("1 | One | BUS_STOP | A | walk=-1"),
+        Err(StopError::BadOption("walk=-1".to_string()))
+    );
+}
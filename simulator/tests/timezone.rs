@@ -0,0 +1,42 @@
+use chrono_tz::{America, Asia, Australia, Europe};
+use matrix_simulator::timezone::posix_tz;
+
+#[test]
+fn irish_summer_time_starts_and_ends_at_1am_utc() {
+    // Last Sunday of March at 01:00 GMT, last Sunday of October at 02:00 IST
+    assert_eq!(posix_tz(Europe::Dublin, 2024), "GMT0IST,M3.5.0/1,M10.5.0");
+    // The same rule whichever day of the month it falls on
+    assert_eq!(
+        posix_tz(Europe::Dublin, 2025),
+        posix_tz(Europe::Dublin, 2024)
+    );
+    assert_eq!(posix_tz(Europe::Berlin, 2024), "CET-1CEST,M3.5.0,M10.5.0/3");
+}
+
+#[test]
+fn offsets_count_west_of_utc() {
+    assert_eq!(posix_tz(America::New_York, 2024), "EST5EDT,M3.2.0,M11.1.0");
+    assert_eq!(posix_tz(Asia::Tokyo, 2024), "JST-9");
+    assert_eq!(posix_tz(Asia::Kolkata, 2024), "IST-5:30");
+}
+
+#[test]
+fn southern_summers_span_the_new_year() {
+    assert_eq!(
+        posix_tz(Australia::Sydney, 2024),
+        "AEST-10AEDT,M10.1.0,M4.1.0/3"
+    );
+    assert_eq!(
+        posix_tz(Australia::Adelaide, 2024),
+        "ACST-9:30ACDT,M10.1.0,M4.1.0/3"
+    );
+}
+
+#[test]
+fn numeric_abbreviations_are_quoted() {
+    assert_eq!(posix_tz(America::Sao_Paulo, 2024), "<-03>3");
+    assert_eq!(
+        posix_tz(Australia::Lord_Howe, 2024),
+        "<+1030>-10:30<+11>-11,M10.1.0,M4.1.0"
+    );
+}
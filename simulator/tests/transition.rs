@@ -0,0 +1,171 @@
+mod common;
+
+use common::{display, dyn_display, COLS, ROWS};
+use embedded_graphics::pixelcolor::BinaryColor;
+use embedded_graphics::prelude::{DrawTarget, Point, PointsIter, Size};
+use embedded_graphics::primitives::Rectangle;
+use embedded_graphics::Pixel;
+use matrix_simulator::mock::RecordingSpi;
+use max7219::{Corner, DynTopology, FrameBuffer, Sample, Topology, Transition, Wiring};
+
+const TRANSITIONS: [Transition; 4] = [
+    Transition::Roll { row_height: 8 },
+    Transition::Wipe,
+    Transition::Dissolve,
+    Transition::SplitFlap {
+        cell: Size::new(5, 8),
+    },
+];
+
+fn area() -> Rectangle {
+    Rectangle::new(Point::zero(), Size::new(24, 16))
+}
+
+fn lit(points: &[Point]) -> impl Iterator<Item = Pixel<BinaryColor>> + '_ {
+    points.iter().map(|point| Pixel(*point, BinaryColor::On))
+}
+
+/// Reads the panel back from what the chips show.
+fn shown(spi: &RecordingSpi) -> FrameBuffer<ROWS, COLS> {
+    let mut frame = FrameBuffer::new(Topology::default());
+    for point in area().points() {
+        let (addr, column, digit) =
+            Topology::<ROWS, COLS>::default().locate(point.x as usize, point.y as usize);
+        if spi.device(addr).digits[digit] & 1 << (7 - column) != 0 {
+            frame.draw_iter([Pixel(point, BinaryColor::On)]).unwrap();
+        }
+    }
+    frame
+}
+
+#[test]
+fn transitions_run_from_all_old_to_all_new() {
+    for transition in TRANSITIONS {
+        for point in area().points() {
+            assert_eq!(transition.sample(&area(), point, 0.0), Sample::Old(point));
+            assert_eq!(transition.sample(&area(), point, 1.0), Sample::New(point));
+        }
+    }
+}
+
+#[test]
+fn roll_moves_every_row_up_separately() {
+    let roll = Transition::Roll { row_height: 8 };
+
+    // Half way, the bottom half of the old row sits on top of the top half of the new one
+    assert_eq!(
+        roll.sample(&area(), Point::new(3, 9), 0.5),
+        Sample::Old(Point::new(3, 13))
+    );
+    assert_eq!(
+        roll.sample(&area(), Point::new(3, 12), 0.5),
+        Sample::New(Point::new(3, 8))
+    );
+}
+
+#[test]
+fn dissolve_brings_pixels_in_gradually() {
+    let new = |progress| {
+        area()
+            .points()
+            .filter(|point| {
+                matches!(
+                    Transition::Dissolve.sample(&area(), *point, progress),
+                    Sample::New(_)
+                )
+            })
+            .count()
+    };
+
+    let total = area().points().count();
+    assert!(new(0.25) < new(0.5) && new(0.5) < new(0.75));
+    assert!((total / 3..total * 2 / 3).contains(&new(0.5)));
+}
+
+#[test]
+fn present_transition_leaves_both_frames_alone() {
+    let (mut display, spi) = display();
+    let old = [Point::new(0, 0), Point::new(9, 9)];
+    let new = [Point::new(1, 1), Point::new(20, 15)];
+
+    display.draw_iter(lit(&old)).unwrap();
+    display.present().unwrap();
+    display.clear(BinaryColor::Off).unwrap();
+    display.draw_iter(lit(&new)).unwrap();
+    let mut expected = FrameBuffer::new(Topology::default());
+    expected.draw_iter(lit(&new)).unwrap();
+
+    // Half way through the wipe, the left hand pixels are new and the right hand ones old
+    display
+        .present_transition(&Transition::Wipe, &area(), 0.5)
+        .unwrap();
+    let half = shown(&spi);
+    assert_eq!(half.pixel(Point::new(1, 1)), BinaryColor::On);
+    assert_eq!(half.pixel(Point::new(0, 0)), BinaryColor::Off);
+    assert_eq!(half.pixel(Point::new(20, 15)), BinaryColor::Off);
+
+    display.present().unwrap();
+    assert!(shown(&spi) == expected);
+}
+
+#[test]
+fn split_flap_leaves_unchanged_cells_still() {
+    let (mut display, spi) = display();
+    let flap = Transition::SplitFlap {
+        cell: Size::new(5, 8),
+    };
+
+    // The first cell keeps its pixel, the last one changes
+    display
+        .draw_iter(lit(&[Point::new(2, 3), Point::new(22, 3)]))
+        .unwrap();
+    display.present().unwrap();
+    display.clear(BinaryColor::Off).unwrap();
+    display
+        .draw_iter(lit(&[Point::new(2, 3), Point::new(22, 6)]))
+        .unwrap();
+
+    for step in 1..10 {
+        display
+            .present_transition(&flap, &area(), step as f32 / 10.0)
+            .unwrap();
+        assert_eq!(shown(&spi).pixel(Point::new(2, 3)), BinaryColor::On);
+    }
+}
+
+#[test]
+fn dynamic_driver_animates_the_same_frames() {
+    let (mut dynamic, dynamic_spi) = dyn_display(DynTopology::new(
+        ROWS,
+        COLS,
+        Corner::BottomLeft,
+        Wiring::Progressive,
+    ));
+    let (mut fixed, fixed_spi) = display();
+
+    let old = [Point::new(0, 0), Point::new(13, 2), Point::new(23, 15)];
+    let new = [Point::new(4, 4), Point::new(13, 10), Point::new(17, 7)];
+
+    dynamic.draw_iter(lit(&old)).unwrap();
+    fixed.draw_iter(lit(&old)).unwrap();
+    dynamic.present().unwrap();
+    fixed.present().unwrap();
+    dynamic.clear(BinaryColor::Off).unwrap();
+    fixed.clear(BinaryColor::Off).unwrap();
+    dynamic.draw_iter(lit(&new)).unwrap();
+    fixed.draw_iter(lit(&new)).unwrap();
+
+    for transition in TRANSITIONS {
+        for step in 0..=8 {
+            let progress = step as f32 / 8.0;
+            dynamic
+                .present_transition(&transition, &area(), progress)
+                .unwrap();
+            fixed
+                .present_transition(&transition, &area(), progress)
+                .unwrap();
+        }
+    }
+
+    assert_eq!(dynamic_spi.transactions(), fixed_spi.transactions());
+}
@@ -0,0 +1,373 @@
+use core::fmt;
+use core::time::Duration;
+use embedded_graphics::image::{Image, ImageRaw};
+use embedded_graphics::pixelcolor::{BinaryColor, Gray8, GrayColor, Rgb888};
+use embedded_graphics::prelude::{DrawTarget, OriginDimensions, Point, Size};
+use embedded_graphics::{Drawable, Pixel};
+use tinybmp::Bmp;
+
+/// GIF frames that don't give a delay are shown this long, like browsers do.
+const DEFAULT_DELAY: Duration = Duration::from_millis(100);
+/// Most pixels an image can have. Files can come from anywhere at runtime, so this keeps
+/// a header claiming a huge size from taking all the memory.
+pub const MAX_PIXELS: u32 = 256 * 256;
+/// Most frames an animation can have.
+pub const MAX_FRAMES: usize = 256;
+/// Most bytes all the frames of an animation can take up once decoded, so a long GIF of
+/// small frames can't take all the memory either.
+pub const MAX_DECODED_BYTES: usize = 64 * 1024;
+
+/// Why an image couldn't be decoded.
+#[derive(Debug)]
+pub enum ImageError {
+    /// The data doesn't start like a BMP, PBM or GIF file.
+    UnknownFormat,
+    Pbm(&'static str),
+    Bmp(tinybmp::ParseError),
+    Gif(gif::DecodingError),
+    /// The file holds no frames to show.
+    Empty,
+    /// The image has more than [`MAX_PIXELS`] pixels.
+    TooLarge(Size),
+    /// The animation has more than [`MAX_FRAMES`] frames.
+    TooManyFrames,
+    /// The frames take up more than [`MAX_DECODED_BYTES`] once decoded.
+    TooMuchData,
+}
+
+impl fmt::Display for ImageError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ImageError::UnknownFormat => write!(f, "not a BMP, PBM or GIF image"),
+            ImageError::Pbm(reason) => write!(f, "bad PBM image: {reason}"),
+            ImageError::Bmp(error) => write!(f, "bad BMP image: {error:?}"),
+            ImageError::Gif(error) => write!(f, "bad GIF image: {error}"),
+            ImageError::Empty => write!(f, "the image has no frames"),
+            ImageError::TooLarge(size) => write!(
+                f,
+                "the image is {}x{}, more than {MAX_PIXELS} pixels",
+                size.width, size.height
+            ),
+            ImageError::TooManyFrames => {
+                write!(f, "the animation has more than {MAX_FRAMES} frames")
+            }
+            ImageError::TooMuchData => write!(
+                f,
+                "the frames take up more than {MAX_DECODED_BYTES} bytes decoded"
+            ),
+        }
+    }
+}
+
+impl std::error::Error for ImageError {}
+
+impl From<tinybmp::ParseError> for ImageError {
+    fn from(error: tinybmp::ParseError) -> Self {
+        ImageError::Bmp(error)
+    }
+}
+
+impl From<gif::DecodingError> for ImageError {
+    fn from(error: gif::DecodingError) -> Self {
+        ImageError::Gif(error)
+    }
+}
+
+/// One picture of an [`Animation`] and how long it stays up.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Frame {
+    width: u32,
+    /// Packed like an `ImageRaw`: rows padded to whole bytes, most significant bit first.
+    pixels: Vec<u8>,
+    delay: Duration,
+}
+
+impl Frame {
+    pub fn delay(&self) -> Duration {
+        self.delay
+    }
+
+    /// Draws the frame with its top left corner at `position`. Unlit pixels are drawn
+    /// too, covering whatever was there.
+    pub fn draw<D>(&self, target: &mut D, position: Point) -> Result<(), D::Error>
+    where
+        D: DrawTarget<Color = BinaryColor>,
+    {
+        let raw = ImageRaw::<BinaryColor>::new(&self.pixels, self.width);
+        Image::new(&raw, position).draw(target)?;
+        Ok(())
+    }
+}
+
+/// A picture or an animation in on/off pixels, decoded from a BMP, PBM or animated GIF.
+///
+/// Colors are thresholded: pixels brighter than mid gray light up, so a logo drawn light
+/// on dark looks the same on the panel. The data can come from `include_bytes!` or from
+/// anywhere at runtime; frames are decoded up front and owned.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Animation {
+    size: Size,
+    frames: Vec<Frame>,
+}
+
+/// A whole image being composed, packed like a [`Frame`].
+struct Canvas {
+    size: Size,
+    pixels: Vec<u8>,
+}
+
+impl Canvas {
+    fn new(size: Size) -> Result<Self, ImageError> {
+        match size.width.checked_mul(size.height) {
+            Some(pixels) if pixels <= MAX_PIXELS => Ok(Canvas {
+                size,
+                pixels: vec![0; size.width.div_ceil(8) as usize * size.height as usize],
+            }),
+            _ => Err(ImageError::TooLarge(size)),
+        }
+    }
+
+    fn set(&mut self, x: u32, y: u32, lit: bool) {
+        if x < self.size.width && y < self.size.height {
+            let stride = self.size.width.div_ceil(8);
+            let byte = &mut self.pixels[(y * stride + x / 8) as usize];
+            let bit = 0x80 >> (x % 8);
+            if lit {
+                *byte |= bit;
+            } else {
+                *byte &= !bit;
+            }
+        }
+    }
+
+    fn frame(&self, delay: Duration) -> Frame {
+        Frame {
+            width: self.size.width,
+            pixels: self.pixels.clone(),
+            delay,
+        }
+    }
+}
+
+/// Thresholds whatever is drawn on it, for decoders that draw rather than hand out pixels.
+impl DrawTarget for Canvas {
+    type Color = Rgb888;
+    type Error = core::convert::Infallible;
+
+    fn draw_iter<I>(&mut self, pixels: I) -> Result<(), Self::Error>
+    where
+        I: IntoIterator<Item = Pixel<Rgb888>>,
+    {
+        for Pixel(point, color) in pixels {
+            if let (Ok(x), Ok(y)) = (u32::try_from(point.x), u32::try_from(point.y)) {
+                self.set(x, y, bright(color));
+            }
+        }
+        Ok(())
+    }
+}
+
+impl OriginDimensions for Canvas {
+    fn size(&self) -> Size {
+        self.size
+    }
+}
+
+fn bright(color: Rgb888) -> bool {
+    Gray8::from(color).luma() >= 0x80
+}
+
+impl Animation {
+    /// Decodes a BMP, PBM or GIF file, telling them apart by their first bytes.
+    pub fn decode(data: &[u8]) -> Result<Self, ImageError> {
+        match data {
+            [b'B', b'M', ..] => Animation::from_bmp(data),
+            [b'P', b'1' | b'4', ..] => Animation::from_pbm(data),
+            [b'G', b'I', b'F', ..] => Animation::from_gif(data),
+            _ => Err(ImageError::UnknownFormat),
+        }
+    }
+
+    /// A still image from a plain (`P1`) or raw (`P4`) PBM file. PBM stores black as 1,
+    /// so the 0 pixels are the ones that light up.
+    pub fn from_pbm(data: &[u8]) -> Result<Self, ImageError> {
+        let raw = match data {
+            [b'P', b'1', ..] => false,
+            [b'P', b'4', ..] => true,
+            _ => return Err(ImageError::Pbm("missing the P1 or P4 magic number")),
+        };
+        let mut rest = &data[2..];
+        let size = Size::new(pbm_number(&mut rest)?, pbm_number(&mut rest)?);
+        let mut canvas = Canvas::new(size)?;
+
+        if raw {
+            // A single whitespace byte, then rows padded to whole bytes
+            let stride = size.width.div_ceil(8) as usize;
+            let bits = rest
+                .get(1..1 + stride * size.height as usize)
+                .ok_or(ImageError::Pbm("the pixels are cut short"))?;
+            for (y, row) in bits.chunks(stride).enumerate() {
+                for x in 0..size.width as usize {
+                    canvas.set(x as u32, y as u32, row[x / 8] & 0x80 >> (x % 8) == 0);
+                }
+            }
+        } else {
+            let mut pixels = rest.iter().filter(|byte| !byte.is_ascii_whitespace());
+            for y in 0..size.height {
+                for x in 0..size.width {
+                    match pixels.next() {
+                        Some(b'0') => canvas.set(x, y, true),
+                        Some(b'1') => canvas.set(x, y, false),
+                        Some(_) => return Err(ImageError::Pbm("pixels must be 0 or 1")),
+                        None => return Err(ImageError::Pbm("the pixels are cut short")),
+                    }
+                }
+            }
+        }
+
+        Ok(Animation {
+            size,
+            frames: vec![canvas.frame(Duration::ZERO)],
+        })
+    }
+
+    /// A still image from a BMP file of any bit depth.
+    pub fn from_bmp(data: &[u8]) -> Result<Self, ImageError> {
+        let bmp = Bmp::<Rgb888>::from_slice(data)?;
+        let mut canvas = Canvas::new(bmp.size())?;
+        // Drawn rather than read through `Bmp::pixels`, which turns bottom up files upside down
+        Image::new(&bmp, Point::zero()).draw(&mut canvas).ok();
+
+        Ok(Animation {
+            size: canvas.size,
+            frames: vec![canvas.frame(Duration::ZERO)],
+        })
+    }
+
+    /// Every frame of a GIF, each composed over the ones before it the way the file asks.
+    pub fn from_gif(data: &[u8]) -> Result<Self, ImageError> {
+        let mut options = gif::DecodeOptions::new();
+        options.set_color_output(gif::ColorOutput::RGBA);
+        let mut decoder = options.read_info(data)?;
+
+        let mut canvas = Canvas::new(Size::new(decoder.width() as u32, decoder.height() as u32))?;
+        let mut frames = Vec::new();
+
+        while let Some(frame) = decoder.read_next_frame()? {
+            // Every frame decodes to a whole canvas, so both limits are known before it is
+            if frames.len() == MAX_FRAMES {
+                return Err(ImageError::TooManyFrames);
+            }
+            if (frames.len() + 1) * canvas.pixels.len() > MAX_DECODED_BYTES {
+                return Err(ImageError::TooMuchData);
+            }
+
+            let before = canvas.pixels.clone();
+            let (left, top, width) = (frame.left as u32, frame.top as u32, frame.width as u32);
+
+            for (index, rgba) in frame.buffer.chunks_exact(4).enumerate() {
+                // Transparent pixels let the frame before show through
+                if rgba[3] != 0 {
+                    let (x, y) = (index as u32 % width, index as u32 / width);
+                    let color = Rgb888::new(rgba[0], rgba[1], rgba[2]);
+                    canvas.set(left + x, top + y, bright(color));
+                }
+            }
+
+            let delay = match frame.delay {
+                0 => DEFAULT_DELAY,
+                hundredths => Duration::from_millis(hundredths as u64 * 10),
+            };
+            frames.push(canvas.frame(delay));
+
+            match frame.dispose {
+                gif::DisposalMethod::Background => {
+                    for y in top..top + frame.height as u32 {
+                        for x in left..left + width {
+                            canvas.set(x, y, false);
+                        }
+                    }
+                }
+                gif::DisposalMethod::Previous => canvas.pixels = before,
+                _ => {}
+            }
+        }
+
+        if frames.is_empty() {
+            return Err(ImageError::Empty);
+        }
+        Ok(Animation {
+            size: canvas.size,
+            frames,
+        })
+    }
+
+    pub fn size(&self) -> Size {
+        self.size
+    }
+
+    pub fn frames(&self) -> &[Frame] {
+        &self.frames
+    }
+
+    /// How long it takes to show every frame once. Zero for a still image.
+    pub fn duration(&self) -> Duration {
+        self.frames.iter().map(|frame| frame.delay).sum()
+    }
+
+    /// The frame up `elapsed` after the animation started, looping round at the end.
+    pub fn frame_at(&self, elapsed: Duration) -> &Frame {
+        let total = self.duration().as_millis();
+        if total == 0 {
+            return &self.frames[0];
+        }
+
+        let mut left = elapsed.as_millis() % total;
+        for frame in &self.frames {
+            match left.checked_sub(frame.delay.as_millis()) {
+                Some(rest) => left = rest,
+                None => return frame,
+            }
+        }
+        &self.frames[0]
+    }
+
+    /// Draws the frame up `elapsed` after the animation started with its top left corner
+    /// at `position`.
+    pub fn draw<D>(
+        &self,
+        target: &mut D,
+        position: Point,
+        elapsed: Duration,
+    ) -> Result<(), D::Error>
+    where
+        D: DrawTarget<Color = BinaryColor>,
+    {
+        self.frame_at(elapsed).draw(target, position)
+    }
+}
+
+/// Reads the next number of a PBM header, skipping whitespace and `#` comments.
+fn pbm_number(rest: &mut &[u8]) -> Result<u32, ImageError> {
+    loop {
+        match rest {
+            [byte, tail @ ..] if byte.is_ascii_whitespace() => *rest = tail,
+            [b'#', ..] => {
+                let end = rest
+                    .iter()
+                    .position(|byte| *byte == b'\n')
+                    .unwrap_or(rest.len());
+                *rest = &rest[end..];
+            }
+            _ => break,
+        }
+    }
+
+    let digits = rest.iter().take_while(|byte| byte.is_ascii_digit()).count();
+    let number = core::str::from_utf8(&rest[..digits])
+        .ok()
+        .and_then(|digits| digits.parse().ok())
+        .ok_or(ImageError::Pbm("expected a number in the header"))?;
+    *rest = &rest[digits..];
+    Ok(number)
+}
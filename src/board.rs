@@ -0,0 +1,244 @@
+use crate::departure::Departure;
+use crate::layout::{Layout, Padding, Zone};
+use crate::marquee::Marquee;
+use chrono::{DateTime, FixedOffset, TimeDelta, Timelike};
+use chrono_tz::Tz;
+use core::time::Duration;
+use embedded_graphics::prelude::{DrawTarget, Size};
+use embedded_graphics::primitives::Rectangle;
+use embedded_graphics::text::renderer::TextRenderer;
+use embedded_graphics::text::{Alignment, Baseline};
+use embedded_graphics::Drawable;
+use embedded_graphics::{pixelcolor::BinaryColor, prelude::Point, text::Text};
+use matrix_fonts::{ProportionalTextStyle, FIGURE_SPACE, FONT_PROP_8, WALK};
+use max7219::Transition;
+
+// Tabular digits keep the minute counts and the clock from jittering as they change
+pub(crate) const CHARACTER_STYLE: ProportionalTextStyle<'static, BinaryColor> =
+    ProportionalTextStyle::new(&FONT_PROP_8, BinaryColor::On).with_tabular_digits(true);
+
+/// Height of a line of text, one matrix row.
+pub(crate) const LINE: u32 = 8;
+
+/// Zone of the [`layout`] holding the clock.
+pub const CLOCK: &str = "clock";
+/// Zone of the [`layout`] holding the status bar, when the panel has room for one.
+pub const STATUS: &str = "status";
+
+/// Whether clocks count the hours to 12 or to 24.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum HourFormat {
+    Twelve,
+    #[default]
+    TwentyFour,
+}
+
+impl HourFormat {
+    /// The hour of `time` as a clock shows it: 1 to 12, or 0 to 23.
+    pub fn hour(self, time: &impl Timelike) -> u32 {
+        match self {
+            HourFormat::Twelve => time.hour12().1,
+            HourFormat::TwentyFour => time.hour(),
+        }
+    }
+}
+
+/// Zone of the [`layout`] holding departure row `index`.
+pub fn row_zone(index: usize) -> String {
+    format!("row{}", index)
+}
+
+/// The transition between departure updates called `name` in the config: "roll", "wipe",
+/// "dissolve" or "flap". Anything else, like "none", switches straight over.
+pub fn transition(name: &str) -> Option<Transition> {
+    match name {
+        "roll" => Some(Transition::Roll { row_height: 8 }),
+        "wipe" => Some(Transition::Wipe),
+        "dissolve" => Some(Transition::Dissolve),
+        "flap" => Some(Transition::SplitFlap {
+            cell: Size::new(FONT_PROP_8.tabular_advance(), LINE),
+        }),
+        _ => None,
+    }
+}
+
+/// The departure board for a panel covering `bounds`: the clock in the top right corner,
+/// `rows` rows of departures from the top down, the first one beside the clock, and a
+/// status bar along the bottom if there is a line to spare.
+pub fn layout(bounds: Rectangle, rows: usize) -> Layout {
+    let clock_width = text_width("00:00");
+
+    let clock = Rectangle::new(
+        bounds.top_left + Point::new(bounds.size.width.saturating_sub(clock_width) as i32, 0),
+        Size::new(clock_width, LINE),
+    );
+    let mut layout =
+        Layout::new().with_zone(CLOCK, Zone::new(clock).with_alignment(Alignment::Right));
+
+    for index in 0..rows {
+        let top_left = bounds.top_left + Point::new(0, (index as u32 * LINE) as i32);
+        let zone = if index == 0 {
+            // Keep a column clear between the first row and the clock
+            Zone::new(Rectangle::new(
+                top_left,
+                Size::new(bounds.size.width.saturating_sub(clock_width), LINE),
+            ))
+            .with_padding(Padding::new(0, 1, 0, 0))
+        } else {
+            Zone::new(Rectangle::new(top_left, Size::new(bounds.size.width, LINE)))
+        };
+        layout = layout.with_zone(row_zone(index), zone);
+    }
+
+    if bounds.size.height >= (rows as u32 + 1) * LINE {
+        let status = Rectangle::new(
+            bounds.top_left + Point::new(0, (bounds.size.height - LINE) as i32),
+            Size::new(bounds.size.width, LINE),
+        );
+        layout = layout.with_zone(STATUS, Zone::new(status).with_alignment(Alignment::Center));
+    }
+
+    layout
+}
+
+pub(crate) fn text_width(text: &str) -> u32 {
+    CHARACTER_STYLE
+        .measure_string(text, Point::zero(), Baseline::Top)
+        .bounding_box
+        .size
+        .width
+}
+
+/// Minutes left at `now` until `scheduled_time`, padded to three characters with a figure
+/// space so it takes the same width as two digits.
+fn format_minutes_until(scheduled_time: DateTime<FixedOffset>, now: DateTime<Tz>) -> String {
+    // Calculate the remaining time in minutes, whatever zone the departure is in
+    let duration_until_departure = scheduled_time.signed_duration_since(now);
+    let n = duration_until_departure.num_minutes();
+    match n {
+        n if n <= 0 => format!("{FIGURE_SPACE}0m"), // If `n` is 0 or less, return "0"
+        1..=9 => format!("{FIGURE_SPACE}{}m", n),   // Pad single-digit positive numbers
+        _ => format!("{}m", n),                     // No padding for numbers 10 and above
+    }
+}
+
+/// The departures with the minutes left at `now` until each, keeping service names whole,
+/// e.g. "|Bray (Daly)  6m +2|Greystones 13m". Ones running late have the minutes they're
+/// behind after them, and cancelled ones say so instead of when they leave. A departure
+/// leaving in the minutes it takes to `walk` to the stop gets the walking icon in front,
+/// as it's time to leave for it, and ones leaving sooner are left out as too late to catch.
+pub fn format_departure_names(
+    departures: &[Option<Departure>; 3],
+    walk: TimeDelta,
+    now: DateTime<Tz>,
+) -> String {
+    departures
+        .iter()
+        .flatten()
+        // The departures were fetched a while ago, so some may have become too close since
+        .filter(|departure| departure.leaves().signed_duration_since(now) >= walk)
+        .map(|departure| {
+            if departure.cancelled {
+                return format!("|{} cancelled", departure.name());
+            }
+
+            let leaves = departure.leaves();
+            let minutes = leaves.signed_duration_since(now).num_minutes();
+            let leave_now = walk > TimeDelta::zero() && minutes == walk.num_minutes();
+            let icon = if leave_now {
+                WALK.to_string()
+            } else {
+                String::new()
+            };
+            let late = match departure.delay().num_minutes() {
+                delay if delay > 0 => format!(" +{delay}"),
+                _ => String::new(),
+            };
+            format!(
+                "|{}{} {}{}",
+                icon,
+                departure.name(),
+                format_minutes_until(leaves, now),
+                late
+            )
+        })
+        .collect()
+}
+
+/// Draws one departure row within `zone`: `prefix` stays put, and `names`, the departures
+/// as [`format_departure_names`] gives them, scroll past it at `speed` pixels a second when
+/// they don't fit.
+pub fn draw_scrolling_departures<D>(
+    display: &mut D,
+    prefix: &str,
+    names: &str,
+    zone: &Zone,
+    elapsed: Duration,
+    speed: u32,
+) -> Result<(), D::Error>
+where
+    D: DrawTarget<Color = BinaryColor>,
+{
+    let content = zone.content();
+    let mut display = zone.clipped(display);
+
+    let prefix_end = Text::with_baseline(prefix, content.top_left, CHARACTER_STYLE, Baseline::Top)
+        .draw(&mut display)?;
+
+    let scrolling = Rectangle::new(
+        Point::new(prefix_end.x, content.top_left.y),
+        Size::new(
+            content.size.width.saturating_sub(text_width(prefix)),
+            content.size.height,
+        ),
+    );
+
+    Marquee::new(names, CHARACTER_STYLE, scrolling)
+        .with_speed(speed)
+        .draw(&mut display, elapsed)
+}
+
+/// Draws the clock within `zone`, counting the hours as `hours` says.
+pub fn draw_clock<D>(
+    display: &mut D,
+    zone: &Zone,
+    current_time: DateTime<Tz>,
+    hours: HourFormat,
+) -> Result<(), D::Error>
+where
+    D: DrawTarget<Color = BinaryColor>,
+{
+    let text = format!(
+        "{:02}:{:02}",
+        hours.hour(&current_time),
+        current_time.minute()
+    );
+
+    Text::with_baseline(
+        &text,
+        zone.origin(text_width(&text)),
+        CHARACTER_STYLE,
+        Baseline::Top,
+    )
+    .draw(&mut zone.clipped(display))?;
+
+    Ok(())
+}
+
+/// Draws `status` in the status bar `zone`, scrolling it at `speed` pixels a second if it
+/// doesn't fit.
+pub fn draw_status<D>(
+    display: &mut D,
+    zone: &Zone,
+    status: &str,
+    elapsed: Duration,
+    speed: u32,
+) -> Result<(), D::Error>
+where
+    D: DrawTarget<Color = BinaryColor>,
+{
+    Marquee::new(status, CHARACTER_STYLE, zone.content())
+        .with_alignment(zone.alignment())
+        .with_speed(speed)
+        .draw(&mut zone.clipped(display), elapsed)
+}
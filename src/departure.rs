@@ -0,0 +1,69 @@
+use chrono::{DateTime, FixedOffset, TimeDelta};
+use serde::Deserialize;
+
+/// How a service gets around.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum Mode {
+    Bus,
+    Train,
+    Tram,
+    #[default]
+    #[serde(other)]
+    Other,
+}
+
+/// The company running a service.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Deserialize)]
+pub struct Operator {
+    #[serde(rename = "operatorCode", default)]
+    pub code: String,
+    #[serde(rename = "operatorName", default)]
+    pub name: String,
+}
+
+/// A service leaving a stop.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Departure {
+    /// The route, e.g. "27B", or for trains something like "DART".
+    #[serde(default)]
+    pub service_number: String,
+    #[serde(default)]
+    pub destination: String,
+    /// e.g. "Northbound" or "Inbound".
+    #[serde(default)]
+    pub direction: String,
+    #[serde(default)]
+    pub operator: Operator,
+    #[serde(rename = "transportMode", default)]
+    pub mode: Mode,
+    /// When the timetable says it leaves.
+    pub scheduled_departure: DateTime<FixedOffset>,
+    /// When it's expected to leave, if it's being tracked.
+    #[serde(default)]
+    pub real_time_departure: Option<DateTime<FixedOffset>>,
+    #[serde(default)]
+    pub cancelled: bool,
+}
+
+impl Departure {
+    /// What the board calls it: trains by where they're going, as every DART has the
+    /// same service number, and everything else by its route.
+    pub fn name(&self) -> &str {
+        match self.mode {
+            Mode::Train => &self.destination,
+            _ => &self.service_number,
+        }
+    }
+
+    /// When it's expected to leave, going by the timetable if it isn't tracked.
+    pub fn leaves(&self) -> DateTime<FixedOffset> {
+        self.real_time_departure.unwrap_or(self.scheduled_departure)
+    }
+
+    /// How far behind the timetable it's running, negative when it's early.
+    pub fn delay(&self) -> TimeDelta {
+        self.leaves() - self.scheduled_departure
+    }
+}
@@ -0,0 +1,123 @@
+use crate::departure::Departure;
+use chrono::{DateTime, TimeDelta};
+use chrono_tz::Tz;
+
+/// Most departures a row has room for.
+pub const MAX_DEPARTURES: usize = 3;
+
+/// Values a field of a departure has to be one of, or mustn't be. Both are compared
+/// ignoring case.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Rule {
+    pub include: Vec<String>,
+    pub exclude: Vec<String>,
+}
+
+impl Rule {
+    /// Whether a departure with `value` gets past. One without the field, an empty
+    /// `value`, only gets past when nothing in particular is asked for.
+    pub fn allows(&self, value: &str) -> bool {
+        let listed = |list: &[String]| list.iter().any(|item| item.eq_ignore_ascii_case(value));
+        (self.include.is_empty() || listed(&self.include)) && !listed(&self.exclude)
+    }
+}
+
+/// Which departures from a stop are worth showing.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Filter {
+    pub destination: Rule,
+    /// The route, or service number, e.g. "27B".
+    pub route: Rule,
+    /// The company running the service, by name, e.g. "Dublin Bus".
+    pub operator: Rule,
+    /// e.g. "Northbound" or "Inbound".
+    pub direction: Rule,
+    /// Departures leaving sooner than this are hidden, as there's no getting to them.
+    pub cutoff: TimeDelta,
+    /// Most departures to show, up to [`MAX_DEPARTURES`].
+    pub max: usize,
+}
+
+impl Default for Filter {
+    fn default() -> Self {
+        Filter {
+            destination: Rule::default(),
+            route: Rule::default(),
+            operator: Rule::default(),
+            direction: Rule::default(),
+            cutoff: TimeDelta::zero(),
+            max: MAX_DEPARTURES,
+        }
+    }
+}
+
+impl Filter {
+    /// Sets one option from a stop's entry in the config: `to`, `route`, `operator` or
+    /// `direction` with a comma separated list to include, the same with `not_` in front
+    /// to exclude, `cutoff` in minutes or `max`. Returns `false` for anything else.
+    pub fn set(&mut self, key: &str, value: &str) -> bool {
+        let list = || -> Vec<String> {
+            value
+                .split(',')
+                .map(str::trim)
+                .filter(|item| !item.is_empty())
+                .map(String::from)
+                .collect()
+        };
+        let (rule, exclude) = match key.strip_prefix("not_") {
+            Some(key) => (key, true),
+            None => (key, false),
+        };
+        let rule = match rule {
+            "to" => &mut self.destination,
+            "route" => &mut self.route,
+            "operator" => &mut self.operator,
+            "direction" => &mut self.direction,
+            _ if exclude => return false,
+            "cutoff" => match value.parse() {
+                Ok(minutes) => {
+                    self.cutoff = TimeDelta::minutes(minutes);
+                    return true;
+                }
+                Err(_) => return false,
+            },
+            "max" => match value.parse() {
+                Ok(max) if max <= MAX_DEPARTURES => {
+                    self.max = max;
+                    return true;
+                }
+                _ => return false,
+            },
+            _ => return false,
+        };
+        match exclude {
+            true => rule.exclude = list(),
+            false => rule.include = list(),
+        }
+        true
+    }
+
+    /// Whether `departure` makes it onto the board at `now`. Cancelled ones are kept, to
+    /// show they aren't coming.
+    pub fn keeps(&self, departure: &Departure, now: DateTime<Tz>) -> bool {
+        self.destination.allows(&departure.destination)
+            && self.route.allows(&departure.service_number)
+            && self.operator.allows(&departure.operator.name)
+            && self.direction.allows(&departure.direction)
+            && departure.leaves().signed_duration_since(now) >= self.cutoff
+    }
+
+    /// The departures that make it onto the board at `now`, in the order they came, up
+    /// to `max` of them.
+    pub fn apply<'a>(
+        &self,
+        departures: impl IntoIterator<Item = &'a Departure>,
+        now: DateTime<Tz>,
+    ) -> Vec<&'a Departure> {
+        departures
+            .into_iter()
+            .filter(|departure| self.keeps(departure, now))
+            .take(self.max.min(MAX_DEPARTURES))
+            .collect()
+    }
+}
@@ -0,0 +1,455 @@
+//! A JSON parser that works a byte at a time, so a response can be read in small chunks
+//! straight off the connection, holding only the items wanted.
+
+use core::fmt;
+use serde::de::DeserializeOwned;
+
+/// Deepest the body may nest objects and arrays.
+const MAX_DEPTH: usize = 16;
+/// Bytes read from the connection at a time.
+const CHUNK: usize = 256;
+
+/// Why the array couldn't be read out of a body.
+#[derive(Debug)]
+pub enum JsonError<E> {
+    /// Reading the body failed.
+    Read(E),
+    /// A byte that can't come where it did, counting from the start of the body.
+    Unexpected { byte: u8, offset: usize },
+    /// The body ended before the array did.
+    Truncated,
+    /// Objects and arrays are nested deeper than the parser keeps track of.
+    TooDeep,
+    /// An item of the array is longer than the limit, in bytes.
+    ItemTooLarge(usize),
+    /// The body has no array under the key.
+    MissingArray,
+    /// An item is well formed JSON, but not what it was read as.
+    Item(serde_json::Error),
+}
+
+impl<E: fmt::Display> fmt::Display for JsonError<E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            JsonError::Read(error) => write!(f, "failed to read the body: {error}"),
+            JsonError::Unexpected { byte, offset } => {
+                write!(f, "unexpected {:?} at byte {offset}", *byte as char)
+            }
+            JsonError::Truncated => write!(f, "the body ended too soon"),
+            JsonError::TooDeep => write!(f, "nested deeper than {MAX_DEPTH} levels"),
+            JsonError::ItemTooLarge(limit) => write!(f, "an item is over {limit} bytes"),
+            JsonError::MissingArray => write!(f, "the array is missing"),
+            JsonError::Item(error) => write!(f, "bad item: {error}"),
+        }
+    }
+}
+
+impl<E: fmt::Debug + fmt::Display> std::error::Error for JsonError<E> {}
+
+/// Reads the first few objects of an array under a key of the body's top level object,
+/// like the departures in `{"stopDepartures": [{...}, {...}]}`, without holding the rest
+/// of the body. Items that aren't objects are skipped.
+#[derive(Clone, Copy, Debug)]
+pub struct ArrayReader<'a> {
+    key: &'a str,
+    max_items: usize,
+    max_item_len: usize,
+}
+
+impl<'a> ArrayReader<'a> {
+    /// Reads the array under `key`, up to 10 items of up to 4 KiB each.
+    pub fn new(key: &'a str) -> Self {
+        ArrayReader {
+            key,
+            max_items: 10,
+            max_item_len: 4 * 1024,
+        }
+    }
+
+    /// Sets how many items to read. Reading stops once there are this many.
+    pub fn with_max_items(mut self, max_items: usize) -> Self {
+        self.max_items = max_items;
+        self
+    }
+
+    /// Sets the longest an item may be, in bytes, which bounds the memory used.
+    pub fn with_max_item_len(mut self, max_item_len: usize) -> Self {
+        self.max_item_len = max_item_len;
+        self
+    }
+
+    /// Reads the items, each deserialized as a `T`, from a body that `read` hands over a
+    /// chunk at a time, filling the buffer it's given and returning how much it filled,
+    /// or 0 at the end.
+    pub fn read<T, E>(
+        &self,
+        mut read: impl FnMut(&mut [u8]) -> Result<usize, E>,
+    ) -> Result<Vec<T>, JsonError<E>>
+    where
+        T: DeserializeOwned,
+    {
+        let mut parser = Parser::new(*self);
+        if self.max_items == 0 {
+            return Ok(parser.items);
+        }
+
+        let mut buf = [0; CHUNK];
+        loop {
+            let len = read(&mut buf).map_err(JsonError::Read)?;
+            if len == 0 {
+                return Err(JsonError::Truncated);
+            }
+            for byte in &buf[..len] {
+                if parser.feed(*byte)? {
+                    return Ok(parser.items);
+                }
+            }
+        }
+    }
+}
+
+/// What the parser expects next.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum State {
+    /// A value: the body, an object member's or an array item.
+    Value,
+    /// After `[`: an item or `]`.
+    FirstItem,
+    /// After `{`: a key or `}`.
+    FirstKey,
+    /// After a `,` in an object.
+    Key,
+    Colon,
+    /// Inside a string, an object key or a value.
+    String {
+        key: bool,
+    },
+    Escape {
+        key: bool,
+    },
+    Unicode {
+        key: bool,
+        left: u8,
+    },
+    Number(Number),
+    /// Partway through `true`, `false` or `null`.
+    Literal {
+        word: &'static [u8],
+        at: usize,
+    },
+    /// After a value: a `,` or the end of what holds it.
+    After,
+}
+
+/// Where a number is up to, following the grammar in RFC 8259.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Number {
+    Minus,
+    Zero,
+    Integer,
+    Point,
+    Fraction,
+    Exponent,
+    ExponentSign,
+    ExponentDigits,
+}
+
+impl Number {
+    /// The next state with `byte`, or `None` if the number can't go on with it.
+    fn next(self, byte: u8) -> Option<Number> {
+        match (self, byte) {
+            (Number::Minus, b'0') => Some(Number::Zero),
+            (Number::Minus | Number::Integer, b'0'..=b'9') => Some(Number::Integer),
+            (Number::Zero | Number::Integer, b'.') => Some(Number::Point),
+            (Number::Point | Number::Fraction, b'0'..=b'9') => Some(Number::Fraction),
+            (Number::Zero | Number::Integer | Number::Fraction, b'e' | b'E') => {
+                Some(Number::Exponent)
+            }
+            (Number::Exponent, b'+' | b'-') => Some(Number::ExponentSign),
+            (Number::Exponent | Number::ExponentSign | Number::ExponentDigits, b'0'..=b'9') => {
+                Some(Number::ExponentDigits)
+            }
+            _ => None,
+        }
+    }
+
+    /// Whether the number could end here.
+    fn complete(self) -> bool {
+        matches!(
+            self,
+            Number::Zero | Number::Integer | Number::Fraction | Number::ExponentDigits
+        )
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Container {
+    Object,
+    Array,
+}
+
+/// Errors of the parser itself, before a read error type comes into it.
+enum ParseError {
+    Unexpected { byte: u8, offset: usize },
+    TooDeep,
+    ItemTooLarge(usize),
+    MissingArray,
+    Item(serde_json::Error),
+}
+
+impl<E> From<ParseError> for JsonError<E> {
+    fn from(error: ParseError) -> Self {
+        match error {
+            ParseError::Unexpected { byte, offset } => JsonError::Unexpected { byte, offset },
+            ParseError::TooDeep => JsonError::TooDeep,
+            ParseError::ItemTooLarge(limit) => JsonError::ItemTooLarge(limit),
+            ParseError::MissingArray => JsonError::MissingArray,
+            ParseError::Item(error) => JsonError::Item(error),
+        }
+    }
+}
+
+struct Parser<'a, T> {
+    reader: ArrayReader<'a>,
+    state: State,
+    stack: Vec<Container>,
+    offset: usize,
+    /// The top level key being read, only as far as it could still be the one wanted.
+    key: Vec<u8>,
+    /// Whether the next value is the one under the key wanted.
+    under_key: bool,
+    /// Whether the array under the key is open.
+    in_array: bool,
+    /// The item being read, if it's one to keep.
+    item: Option<Vec<u8>>,
+    items: Vec<T>,
+}
+
+impl<'a, T: DeserializeOwned> Parser<'a, T> {
+    fn new(reader: ArrayReader<'a>) -> Self {
+        Parser {
+            reader,
+            state: State::Value,
+            stack: Vec::with_capacity(MAX_DEPTH),
+            offset: 0,
+            key: Vec::with_capacity(reader.key.len() + 1),
+            under_key: false,
+            in_array: false,
+            item: None,
+            items: Vec::with_capacity(reader.max_items),
+        }
+    }
+
+    /// Takes the next byte of the body. Returns `true` once there's nothing more to read.
+    fn feed(&mut self, byte: u8) -> Result<bool, ParseError> {
+        let unexpected = ParseError::Unexpected {
+            byte,
+            offset: self.offset,
+        };
+        self.offset += 1;
+
+        if let Some(item) = &mut self.item {
+            if item.len() == self.reader.max_item_len {
+                return Err(ParseError::ItemTooLarge(self.reader.max_item_len));
+            }
+            item.push(byte);
+        }
+
+        match self.state {
+            State::Value
+            | State::FirstItem
+            | State::FirstKey
+            | State::Key
+            | State::Colon
+            | State::After
+                if byte.is_ascii_whitespace() =>
+            {
+                Ok(false)
+            }
+
+            State::Value | State::FirstItem => {
+                if self.under_key && byte != b'[' {
+                    return Err(ParseError::MissingArray);
+                }
+                match byte {
+                    b'{' => self.open(Container::Object, byte),
+                    // Only a JSON object has keys to look under
+                    _ if self.stack.is_empty() => Err(unexpected),
+                    b'[' => self.open(Container::Array, byte),
+                    b']' if self.state == State::FirstItem => self.close(),
+                    b'"' => {
+                        self.state = State::String { key: false };
+                        Ok(false)
+                    }
+                    b'-' => self.number(Number::Minus),
+                    b'0' => self.number(Number::Zero),
+                    b'1'..=b'9' => self.number(Number::Integer),
+                    b't' => self.literal(b"true"),
+                    b'f' => self.literal(b"false"),
+                    b'n' => self.literal(b"null"),
+                    _ => Err(unexpected),
+                }
+            }
+
+            State::FirstKey | State::Key => match byte {
+                b'"' => {
+                    self.key.clear();
+                    self.state = State::String { key: true };
+                    Ok(false)
+                }
+                b'}' if self.state == State::FirstKey => self.close(),
+                _ => Err(unexpected),
+            },
+
+            State::Colon => match byte {
+                b':' => {
+                    self.state = State::Value;
+                    Ok(false)
+                }
+                _ => Err(unexpected),
+            },
+
+            State::String { key } => {
+                match byte {
+                    b'"' if key => {
+                        self.under_key =
+                            self.stack.len() == 1 && self.key == self.reader.key.as_bytes();
+                        self.state = State::Colon;
+                    }
+                    b'"' => self.state = State::After,
+                    b'\\' => self.state = State::Escape { key },
+                    0..=0x1f => return Err(unexpected),
+                    _ => {}
+                }
+                // Escaped keys never match, which is fine for the plain ones looked for
+                if key && self.stack.len() == 1 && self.key.len() <= self.reader.key.len() {
+                    self.key.push(byte);
+                }
+                Ok(false)
+            }
+
+            State::Escape { key } => {
+                self.state = match byte {
+                    b'"' | b'\\' | b'/' | b'b' | b'f' | b'n' | b'r' | b't' => State::String { key },
+                    b'u' => State::Unicode { key, left: 4 },
+                    _ => return Err(unexpected),
+                };
+                Ok(false)
+            }
+
+            State::Unicode { key, left } => {
+                if !byte.is_ascii_hexdigit() {
+                    return Err(unexpected);
+                }
+                self.state = match left {
+                    1 => State::String { key },
+                    left => State::Unicode {
+                        key,
+                        left: left - 1,
+                    },
+                };
+                Ok(false)
+            }
+
+            State::Number(number) => match number.next(byte) {
+                Some(number) => {
+                    self.state = State::Number(number);
+                    Ok(false)
+                }
+                // The byte after a number belongs to whatever comes next
+                None if number.complete() => {
+                    self.state = State::After;
+                    self.offset -= 1;
+                    self.after(byte)
+                }
+                None => Err(unexpected),
+            },
+
+            State::Literal { word, at } => {
+                if byte != word[at] {
+                    return Err(unexpected);
+                }
+                self.state = match at + 1 == word.len() {
+                    true => State::After,
+                    false => State::Literal { word, at: at + 1 },
+                };
+                Ok(false)
+            }
+
+            State::After => self.after(byte),
+        }
+    }
+
+    /// Takes a byte after a value.
+    fn after(&mut self, byte: u8) -> Result<bool, ParseError> {
+        let unexpected = ParseError::Unexpected {
+            byte,
+            offset: self.offset,
+        };
+        self.offset += 1;
+
+        match (byte, self.stack.last()) {
+            (byte, _) if byte.is_ascii_whitespace() => Ok(false),
+            (b',', Some(Container::Object)) => {
+                self.state = State::Key;
+                Ok(false)
+            }
+            (b',', Some(Container::Array)) => {
+                self.state = State::Value;
+                Ok(false)
+            }
+            (b'}', Some(Container::Object)) | (b']', Some(Container::Array)) => self.close(),
+            _ => Err(unexpected),
+        }
+    }
+
+    fn open(&mut self, container: Container, byte: u8) -> Result<bool, ParseError> {
+        if self.stack.len() == MAX_DEPTH {
+            return Err(ParseError::TooDeep);
+        }
+
+        if self.under_key {
+            self.under_key = false;
+            self.in_array = true;
+        } else if self.in_array && self.stack.len() == 2 && container == Container::Object {
+            self.item = Some(vec![byte]);
+        }
+
+        self.stack.push(container);
+        self.state = match container {
+            Container::Object => State::FirstKey,
+            Container::Array => State::FirstItem,
+        };
+        Ok(false)
+    }
+
+    fn close(&mut self) -> Result<bool, ParseError> {
+        self.stack.pop();
+        self.state = State::After;
+
+        match self.stack.len() {
+            // The end of an item
+            2 if self.item.is_some() => {
+                let item = self.item.take().unwrap_or_default();
+                let value = serde_json::from_slice(&item).map_err(ParseError::Item)?;
+                self.items.push(value);
+                Ok(self.items.len() == self.reader.max_items)
+            }
+            // The end of the array
+            1 if self.in_array => Ok(true),
+            // The end of the body, without the array in it
+            0 => Err(ParseError::MissingArray),
+            _ => Ok(false),
+        }
+    }
+
+    fn number(&mut self, number: Number) -> Result<bool, ParseError> {
+        self.state = State::Number(number);
+        Ok(false)
+    }
+
+    fn literal(&mut self, word: &'static [u8]) -> Result<bool, ParseError> {
+        self.state = State::Literal { word, at: 1 };
+        Ok(false)
+    }
+}
@@ -0,0 +1,134 @@
+use embedded_graphics::draw_target::{Clipped, DrawTargetExt};
+use embedded_graphics::prelude::{DrawTarget, Point, Size};
+use embedded_graphics::primitives::Rectangle;
+use embedded_graphics::text::Alignment;
+
+/// Blank space kept clear inside the edges of a [`Zone`], in pixels.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Padding {
+    pub top: u32,
+    pub right: u32,
+    pub bottom: u32,
+    pub left: u32,
+}
+
+impl Padding {
+    pub const fn new(top: u32, right: u32, bottom: u32, left: u32) -> Self {
+        Padding {
+            top,
+            right,
+            bottom,
+            left,
+        }
+    }
+}
+
+/// A rectangular part of the panel that one widget draws into.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Zone {
+    area: Rectangle,
+    padding: Padding,
+    alignment: Alignment,
+}
+
+impl Zone {
+    /// A left aligned zone covering `area`, without padding.
+    pub fn new(area: Rectangle) -> Self {
+        Zone {
+            area,
+            padding: Padding::default(),
+            alignment: Alignment::Left,
+        }
+    }
+
+    pub fn with_padding(mut self, padding: Padding) -> Self {
+        self.padding = padding;
+        self
+    }
+
+    /// Sets where content narrower than the zone goes.
+    pub fn with_alignment(mut self, alignment: Alignment) -> Self {
+        self.alignment = alignment;
+        self
+    }
+
+    /// The whole zone, padding included.
+    pub fn area(&self) -> Rectangle {
+        self.area
+    }
+
+    pub fn alignment(&self) -> Alignment {
+        self.alignment
+    }
+
+    /// The part of the zone left for content once padded.
+    pub fn content(&self) -> Rectangle {
+        let Padding {
+            top,
+            right,
+            bottom,
+            left,
+        } = self.padding;
+
+        Rectangle::new(
+            self.area.top_left + Point::new(left as i32, top as i32),
+            Size::new(
+                self.area.size.width.saturating_sub(left + right),
+                self.area.size.height.saturating_sub(top + bottom),
+            ),
+        )
+    }
+
+    /// Top left corner of content `width` pixels wide, placed by the zone's alignment.
+    pub fn origin(&self, width: u32) -> Point {
+        let content = self.content();
+        let spare = content.size.width.saturating_sub(width) as i32;
+
+        content.top_left
+            + match self.alignment {
+                Alignment::Left => Point::zero(),
+                Alignment::Center => Point::new(spare / 2, 0),
+                Alignment::Right => Point::new(spare, 0),
+            }
+    }
+
+    /// `display`, clipped to the zone's content so nothing drawn spills into its neighbours.
+    pub fn clipped<'a, D>(&self, display: &'a mut D) -> Clipped<'a, D>
+    where
+        D: DrawTarget,
+    {
+        display.clipped(&self.content())
+    }
+}
+
+/// A set of named [`Zone`]s making up the panel.
+#[derive(Clone, Debug, Default)]
+pub struct Layout {
+    zones: Vec<(String, Zone)>,
+}
+
+impl Layout {
+    pub fn new() -> Self {
+        Layout::default()
+    }
+
+    /// Adds `zone` as `name`, replacing any zone of that name.
+    pub fn with_zone(mut self, name: impl Into<String>, zone: Zone) -> Self {
+        let name = name.into();
+        self.zones.retain(|(existing, _)| *existing != name);
+        self.zones.push((name, zone));
+        self
+    }
+
+    pub fn zone(&self, name: &str) -> Option<&Zone> {
+        self.zones
+            .iter()
+            .find(|(existing, _)| existing == name)
+            .map(|(_, zone)| zone)
+    }
+
+    /// Every zone with its name, in the order they were added.
+    pub fn zones(&self) -> impl Iterator<Item = (&str, &Zone)> {
+        self.zones.iter().map(|(name, zone)| (name.as_str(), zone))
+    }
+}
@@ -1,20 +1,23 @@
-#![feature(generic_const_exprs)]
-mod max7219;
+mod animation;
+mod board;
+mod departure;
+mod filter;
+mod json;
+mod layout;
+mod marquee;
+mod schedule;
+mod screen;
+mod stops;
+mod timezone;
 mod wifi;
+use animation::Animation;
 use anyhow::Result as ResultAny;
-use chrono::{DateTime, Timelike, Utc};
-use chrono_tz::Europe::Dublin;
+use board::HourFormat;
+use chrono::{DateTime, Datelike, NaiveTime, Utc};
 use chrono_tz::Tz;
-use embedded_graphics::mono_font::ascii::FONT_5X8;
+use embedded_graphics::geometry::AnchorPoint;
+use embedded_graphics::pixelcolor::BinaryColor;
 use embedded_graphics::prelude::{Dimensions, DrawTarget};
-use embedded_graphics::text::Baseline;
-use embedded_graphics::Drawable;
-use embedded_graphics::{
-    mono_font::MonoTextStyle,
-    pixelcolor::BinaryColor,
-    prelude::Point,
-    text::Text,
-};
 use esp_idf_svc::eventloop::EspSystemEventLoop;
 use esp_idf_svc::hal::units::FromValueType;
 use esp_idf_svc::hal::{
@@ -22,9 +25,31 @@ use esp_idf_svc::hal::{
     prelude::Peripherals,
     spi::{config, SpiDeviceDriver, SpiDriverConfig},
 };
+use esp_idf_svc::nvs::{EspDefaultNvsPartition, EspNvs};
+use max7219::{Corner, DynMax7219, DynTopology, Wiring};
+use schedule::{Dimming, DimmingSchedule};
+use screen::{
+    ClockScreen, Departures, DeparturesScreen, MessageScreen, Rotation, ScreenKind, WeatherScreen,
+};
 use std::thread;
-use std::time::Duration;
-use wifi::{post_with_time, Location};
+use std::time::{Duration, Instant};
+use stops::Stop;
+use wifi::post_with_time;
+
+/// How often the schedule is checked while the panel is off.
+const OFF_INTERVAL: Duration = Duration::from_secs(20);
+/// Time between frames while text scrolls.
+const FRAME_INTERVAL: Duration = Duration::from_millis(50);
+/// Length of the transition between departure updates, in frames.
+const TRANSITION_FRAMES: u32 = 12;
+/// Played once at boot, unless `splash_url` points at another image.
+const SPLASH: &[u8] = include_bytes!("../assets/splash.gif");
+/// How long a splash that is a still image stays up.
+const STILL_SPLASH: Duration = Duration::from_secs(2);
+/// NVS namespace for settings that can change without reflashing.
+const NVS_NAMESPACE: &str = "matrix";
+/// Longest stop list NVS hands back, in bytes.
+const MAX_STOPS_LEN: usize = 1024;
 
 #[toml_cfg::toml_config]
 pub struct Config {
@@ -34,41 +59,127 @@ pub struct Config {
     wifi_psk: &'static str,
     #[default("")]
     api_tfi: &'static str,
+    /// Stops on the departure board, one per line or between semicolons, each as
+    /// "stop ID | name | type | label | row" and then options like "walk=9" for the minutes
+    /// it takes to get there or filters like "to=Greystones". A "stops" string in NVS
+    /// takes over from this
+    #[default("8220IR3881 | Killester | TRAIN_STATION | KI | walk=9 | to=Dublin Connolly,Greystones,Bray (Daly); 8220DB000529 | Collins Avenue, Killester | BUS_STOP | CA | walk=4; 8220DB000609 | Castle Grove, Clontarf | BUS_STOP | CG | walk=2")]
+    stops: &'static str,
+    /// Size of the panel, in modules
+    #[default(3)]
+    panel_rows: usize,
+    #[default(15)]
+    panel_cols: usize,
+    /// Corner of the panel with the module furthest along the chain from the board:
+    /// "top_left", "top_right", "bottom_left" or "bottom_right"
+    #[default("bottom_left")]
+    panel_corner: &'static str,
+    /// How the chain runs from one row of modules to the next: "progressive" if every row
+    /// starts on the same side, "serpentine" if it snakes back and forth
+    #[default("progressive")]
+    panel_wiring: &'static str,
+    /// Perceived panel brightness during the day, 0-100%
+    #[default(20)]
+    brightness: u8,
+    /// Perceived panel brightness at night, 0-100%
+    #[default(0)]
+    night_brightness: u8,
+    /// How long the brightness takes to ramp between night and day around sunrise and sunset
+    #[default(60)]
+    dimming_minutes: i64,
+    /// Where the panel is, to work out sunrise and sunset
+    #[default(53.3498)]
+    latitude: f64,
+    #[default(-6.2603)]
+    longitude: f64,
+    /// How fast departures too long for their row scroll, in pixels per second
+    #[default(20)]
+    scroll_speed: u32,
+    /// Animation between screens and departure updates: "roll", "wipe", "dissolve", "flap" or "none"
+    #[default("roll")]
+    transition: &'static str,
+    /// Optional window to turn the panel off overnight, as local "HH:MM" times
+    #[default("")]
+    display_off_from: &'static str,
+    #[default("")]
+    display_off_until: &'static str,
+    /// Screens to rotate through, in order, each with an optional number of seconds to show
+    /// it for: "departures", "clock", "weather" and "message"
+    #[default("departures")]
+    screens: &'static str,
+    /// Local time zone for the clock, the departures and the off window, as an IANA name
+    /// like "Europe/Dublin"
+    #[default("Europe/Dublin")]
+    timezone: &'static str,
+    /// Optional POSIX TZ string for the C library, e.g. "GMT0IST,M3.5.0/1,M10.5.0", for
+    /// when the one worked out from `timezone` isn't right
+    #[default("")]
+    posix_tz: &'static str,
+    /// Count the hours to 24 rather than to 12 with AM and PM
+    #[default(false)]
+    clock_24h: bool,
+    /// Blink the colon of the clock screen every second
+    #[default(true)]
+    clock_blink: bool,
+    /// Show the date under the time on the clock screen, on panels with room for it
+    #[default(true)]
+    clock_date: bool,
+    /// Text for the message screen, with a line break between rows
+    #[default("")]
+    message: &'static str,
+    /// Optional BMP, PBM or GIF to play at boot instead of the built in splash
+    #[default("")]
+    splash_url: &'static str,
 }
 
-fn format_departure_times(departures: [Option<(String, DateTime<Tz>)>; 3]) -> String {
-    // Get the current time in UTC
-    let current_time = Utc::now().with_timezone(&chrono_tz::Tz::Europe__Dublin);
-
-    let mut text = String::new();
-
-    // Loop through each departure and calculate the remaining minutes
-    for departure in departures.iter() {
-        if let Some((service_number, scheduled_time)) = departure {
-            // Calculate the remaining time in minutes
-            let duration_until_departure = *scheduled_time - current_time;
-            let departure = {
-                let n = duration_until_departure.num_minutes();
-                match n {
-                    n if n <= 0 => format!(" 0m"), // If `n` is 0 or less, return "0"
-                    1..=9 => format!(" {}m", n), // Add a leading space for single-digit positive numbers
-                    _ => format!("{}m", n),      // No space for numbers 10 and above
-                }
-            };
-
-            text.push_str(&format!(
-                "|{} {}",
-                if service_number.len() < 2 {
-                    format!("{} ", service_number) // Add a trailing space if less than 2 characters
-                } else {
-                    service_number.chars().take(2).collect() // Take only the first 2 characters if 2 or more
-                },
-                departure
-            ));
+/// Departures from every stop, in row order, or `None` if any of them can't be had. Each
+/// stop is asked for the ones leaving once there's been time to walk to it.
+fn fetch_departures(
+    api_key: &str,
+    stops: &[Stop],
+    now: DateTime<Tz>,
+) -> Option<Vec<(String, Departures)>> {
+    let mut rows = Vec::with_capacity(stops.len());
+    for stop in stops {
+        let departures = match post_with_time(api_key, now + stop.walk, stop) {
+            Ok(departures) => departures,
+            Err(e) => {
+                log::error!("Failed to fetch departures: {}", e);
+                return None;
+            }
+        };
+
+        rows.push((stop.label.clone(), departures));
+    }
+    Some(rows)
+}
+
+/// The stop list saved in NVS under "stops", or the one in the config if there isn't one
+/// or it can't be read.
+fn load_stops(config: &str) -> ResultAny<Vec<Stop>> {
+    let nvs = EspNvs::new(EspDefaultNvsPartition::take()?, NVS_NAMESPACE, true)?;
+    let mut buf = [0; MAX_STOPS_LEN];
+    match nvs.get_str("stops", &mut buf) {
+        Ok(Some(spec)) => match stops::parse_stops(spec) {
+            Ok(stops) => return Ok(stops),
+            Err(e) => log::error!("Failed to parse the stops from NVS: {}", e),
+        },
+        Ok(None) => {}
+        Err(e) => log::error!("Failed to read the stops from NVS: {}", e),
+    }
+    Ok(stops::parse_stops(config)?)
+}
+
+/// Loads the boot splash, from `url` if one is set and falling back to the built in one.
+fn splash(url: &str) -> ResultAny<Animation> {
+    if !url.is_empty() {
+        match wifi::download(url).map(|data| Animation::decode(&data)) {
+            Ok(Ok(animation)) => return Ok(animation),
+            Ok(Err(e)) => log::error!("Failed to decode the splash: {}", e),
+            Err(e) => log::error!("Failed to download the splash: {}", e),
         }
     }
-    // Return the formatted string
-    text
+    Ok(Animation::decode(SPLASH)?)
 }
 
 fn main() -> ResultAny<()> {
@@ -82,6 +193,7 @@ fn main() -> ResultAny<()> {
     let sysloop = EspSystemEventLoop::take()?;
 
     let app_config = CONFIG;
+    let stops = load_stops(app_config.stops)?;
     let _wifi = wifi::wifi(
         app_config.wifi_ssid,
         app_config.wifi_psk,
@@ -105,63 +217,168 @@ fn main() -> ResultAny<()> {
         &config,
     )?;
 
-    let mut display: max7219::Max7219<_, 3, 15> = max7219::Max7219::new(device);
+    let corner: Corner = app_config
+        .panel_corner
+        .parse()
+        .map_err(|e| anyhow::anyhow!("Unknown panel corner {}: {}", app_config.panel_corner, e))?;
+    let wiring: Wiring = app_config
+        .panel_wiring
+        .parse()
+        .map_err(|e| anyhow::anyhow!("Unknown panel wiring {}: {}", app_config.panel_wiring, e))?;
+    let topology = DynTopology::new(app_config.panel_rows, app_config.panel_cols, corner, wiring);
+    let buffer = vec![[0; 8]; topology.buffer_len()];
+    let mut display = DynMax7219::new(device, topology, buffer);
 
     // make sure to wake the display up
     display.init()?;
+
+    // Play the splash through once, in the middle of the panel, each frame for as long
+    // as the image says
+    let splash = splash(app_config.splash_url)?;
+    let position = display
+        .bounding_box()
+        .resized(splash.size(), AnchorPoint::Center)
+        .top_left;
+    display.set_brightness(app_config.brightness)?;
     display.power_on()?;
+    for frame in splash.frames() {
+        display.clear(BinaryColor::Off)?;
+        frame.draw(&mut display, position)?;
+        display.present()?;
+        thread::sleep(match frame.delay() {
+            Duration::ZERO => STILL_SPLASH,
+            delay => delay,
+        });
+    }
+    display.power_off()?;
+
+    let mut schedule = DimmingSchedule::new(
+        app_config.latitude,
+        app_config.longitude,
+        app_config.brightness,
+        app_config.night_brightness,
+        chrono::Duration::minutes(app_config.dimming_minutes),
+    );
+    if !app_config.display_off_from.is_empty() {
+        schedule = schedule.with_off_window(
+            NaiveTime::parse_from_str(app_config.display_off_from, "%H:%M")?,
+            NaiveTime::parse_from_str(app_config.display_off_until, "%H:%M")?,
+        );
+    }
+
+    // The display is powered off after init, the schedule wakes it up
+    let mut dimming = Dimming::Off;
+
+    let timezone: Tz = app_config
+        .timezone
+        .parse()
+        .map_err(|e| anyhow::anyhow!("Unknown timezone {}: {}", app_config.timezone, e))?;
+
+    // Let the C library's localtime follow the same zone, summer time included
+    let posix_tz = match app_config.posix_tz {
+        "" => timezone::posix_tz(timezone, Utc::now().with_timezone(&timezone).year()),
+        posix_tz => posix_tz.to_string(),
+    };
+    log::info!("Timezone {} ({})", timezone, posix_tz);
+    std::env::set_var("TZ", &posix_tz);
+    unsafe { esp_idf_svc::sys::tzset() };
+    let hours = match app_config.clock_24h {
+        true => HourFormat::TwentyFour,
+        false => HourFormat::Twelve,
+    };
+
+    let bounds = display.bounding_box();
+    let mut rotation = Rotation::new();
+    for slot in screen::parse_rotation(app_config.screens)? {
+        let (api_tfi, speed) = (app_config.api_tfi, app_config.scroll_speed);
+        let (latitude, longitude) = (app_config.latitude, app_config.longitude);
+        rotation = match slot.kind {
+            ScreenKind::Departures => rotation.with_screen(
+                DeparturesScreen::new(board::layout(bounds, stops.len()), {
+                    let stops = stops.clone();
+                    move |now| fetch_departures(api_tfi, &stops, now)
+                })
+                .with_speed(speed)
+                .with_hour_format(hours)
+                .with_walk_times(stops.iter().map(|stop| stop.walk).collect()),
+                slot.dwell,
+            ),
+            ScreenKind::Clock => rotation.with_screen(
+                ClockScreen::new(timezone)
+                    .with_hour_format(hours)
+                    .with_blinking_colon(app_config.clock_blink)
+                    .with_date(app_config.clock_date),
+                slot.dwell,
+            ),
+            ScreenKind::Weather => rotation.with_screen(
+                WeatherScreen::new(move || match wifi::weather(latitude, longitude) {
+                    Ok(weather) => Some(weather),
+                    Err(e) => {
+                        log::error!("Failed to fetch the weather: {}", e);
+                        None
+                    }
+                })
+                .with_speed(speed),
+                slot.dwell,
+            ),
+            ScreenKind::Message => rotation.with_screen(
+                MessageScreen::new(app_config.message, bounds).with_speed(speed),
+                slot.dwell,
+            ),
+        };
+    }
 
-    let character_style = MonoTextStyle::new(&FONT_5X8, BinaryColor::On);
+    let started = Instant::now();
+    let transition = board::transition(app_config.transition);
 
     loop {
-        let current_time = Utc::now().with_timezone(&Dublin);
-        let dublin_time = current_time + chrono::Duration::minutes(4);
-
-        // Define locations with a flag to indicate if map processing is required
-        let locations = [
-            ("KI", Location::Killester, Point::new(0, 0)), // true for mapping
-            ("CA", Location::CollinsAvenue, Point::new(0, 8)), // false, no mapping
-            ("CG", Location::CastleGrove, Point::new(0, 16)), // false, no mapping
-        ];
-
-        for (prefix, location, pos) in &locations {
-            let departures = post_with_time(app_config.api_tfi, dublin_time, *location)?;
-
-            // Apply the map function only if `use_map` is true (i.e., only for Killester)
-            let departures = match location {
-                Location::Killester => departures.map(|entry| match entry {
-                    Some((ref text, _)) if text == "Dublin Connolly" => entry,
-                    Some((ref text, _)) if text == "Greystones" => entry,
-                    Some((ref text, _)) if text == "Bray (Daly)" => entry,
-                    _ => None,
-                }),
-                _ => departures,
-            };
-
-            let text = format!("{}{}", prefix, format_departure_times(departures));
-            Text::with_baseline(
-                &text,
-                display.bounding_box().top_left + *pos,
-                character_style,
-                Baseline::Top,
-            )
-            .draw(&mut display)?;
+        let current_time = Utc::now().with_timezone(&timezone);
+
+        let next_dimming = schedule.at(current_time);
+        if next_dimming != dimming {
+            match next_dimming {
+                Dimming::Off => display.power_off()?,
+                Dimming::On(percent) => {
+                    display.set_brightness(percent)?;
+                    if dimming == Dimming::Off {
+                        display.power_on()?;
+                    }
+                }
+            }
+            dimming = next_dimming;
         }
 
-        // Draw the updated clock
-        Text::with_baseline(
-            &format!("{:02}:{:02}", current_time.hour() % 12, current_time.minute()),
-            display.bounding_box().top_left + Point::new(95, 0),
-            character_style,
-            Baseline::Top,
-        )
-        .draw(&mut display)?;
+        // Screens are only updated while they're up, so nothing is fetched while it's dark
+        if dimming == Dimming::Off {
+            thread::sleep(OFF_INTERVAL);
+            continue;
+        }
 
-        display.flush()?;
+        // Screens fetch every so often, but the scrolling needs a steady frame rate
+        let uptime = started.elapsed();
+        let switched = rotation.advance(uptime);
+        let elapsed = rotation.elapsed(uptime);
+        let mut refresh = switched;
 
-        thread::sleep(Duration::from_secs(20));
-        
         display.clear(BinaryColor::Off)?;
+        if let Some(screen) = rotation.current() {
+            refresh |= screen.update(current_time);
+            screen.draw(&mut display, current_time, elapsed)?;
+        }
+
+        // Animate to the next screen, or from the old departures to the new ones
+        if let (true, Some(transition)) = (refresh, transition) {
+            let area = display.bounding_box();
+            for step in 1..TRANSITION_FRAMES {
+                let progress = step as f32 / TRANSITION_FRAMES as f32;
+                display.present_transition(&transition, &area, progress)?;
+                thread::sleep(FRAME_INTERVAL);
+            }
+        }
+
+        // Only the digits that moved since the last frame go out
+        display.present()?;
 
+        thread::sleep(FRAME_INTERVAL);
     }
 }
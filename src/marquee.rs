@@ -0,0 +1,119 @@
+use core::time::Duration;
+use embedded_graphics::pixelcolor::BinaryColor;
+use embedded_graphics::prelude::{DrawTarget, DrawTargetExt, Point};
+use embedded_graphics::primitives::Rectangle;
+use embedded_graphics::text::renderer::TextRenderer;
+use embedded_graphics::text::{Alignment, Baseline, Text};
+use embedded_graphics::Drawable;
+
+/// Scrolls a line of text too wide for its area from right to left, one pixel at a time,
+/// and wraps it around with a gap. Text that fits is drawn as it is.
+///
+/// Nothing is drawn outside `area`, so the rest of the panel can stay static.
+pub struct Marquee<'a, S> {
+    text: &'a str,
+    style: S,
+    area: Rectangle,
+    speed: u32,
+    gap: u32,
+    alignment: Alignment,
+}
+
+impl<'a, S> Marquee<'a, S>
+where
+    S: TextRenderer<Color = BinaryColor> + Clone,
+{
+    /// Scrolls at 20 pixels a second, with a 10 pixel gap before the text comes round again.
+    pub fn new(text: &'a str, style: S, area: Rectangle) -> Self {
+        Marquee {
+            text,
+            style,
+            area,
+            speed: 20,
+            gap: 10,
+            alignment: Alignment::Left,
+        }
+    }
+
+    /// Sets the scrolling speed in pixels per second. 0 stops the text at its start.
+    pub fn with_speed(mut self, speed: u32) -> Self {
+        self.speed = speed;
+        self
+    }
+
+    /// Sets the blank space between the end of the text and its next start, in pixels.
+    pub fn with_gap(mut self, gap: u32) -> Self {
+        self.gap = gap;
+        self
+    }
+
+    /// Sets where text that fits goes within the area.
+    pub fn with_alignment(mut self, alignment: Alignment) -> Self {
+        self.alignment = alignment;
+        self
+    }
+
+    fn text_width(&self) -> u32 {
+        self.style
+            .measure_string(self.text, Point::zero(), Baseline::Top)
+            .bounding_box
+            .size
+            .width
+    }
+
+    /// How far the text has scrolled left after `elapsed`, in pixels.
+    pub fn offset(&self, elapsed: Duration) -> u32 {
+        let width = self.text_width();
+        if width <= self.area.size.width {
+            return 0;
+        }
+
+        let travelled = elapsed.as_millis() * self.speed as u128 / 1000;
+        (travelled % (width + self.gap) as u128) as u32
+    }
+
+    /// How long the text takes to scroll round once, zero if it fits and stays put.
+    pub fn lap(&self) -> Duration {
+        let width = self.text_width();
+        if width <= self.area.size.width || self.speed == 0 {
+            return Duration::ZERO;
+        }
+
+        Duration::from_millis((width + self.gap) as u64 * 1000 / self.speed as u64)
+    }
+
+    /// Draws the text as it stands `elapsed` into scrolling.
+    pub fn draw<D>(&self, target: &mut D, elapsed: Duration) -> Result<(), D::Error>
+    where
+        D: DrawTarget<Color = BinaryColor>,
+    {
+        let mut clipped = target.clipped(&self.area);
+        let offset = self.offset(elapsed) as i32;
+
+        let spare = self.area.size.width.saturating_sub(self.text_width()) as i32;
+        let start = self.area.top_left
+            + match self.alignment {
+                Alignment::Left => Point::zero(),
+                Alignment::Center => Point::new(spare / 2, 0),
+                Alignment::Right => Point::new(spare, 0),
+            }
+            - Point::new(offset, 0);
+
+        Text::with_baseline(self.text, start, self.style.clone(), Baseline::Top)
+            .draw(&mut clipped)?;
+
+        // The start of the next lap, once the end of this one is in view
+        if offset > 0 {
+            let lap = (self.text_width() + self.gap) as i32;
+            Text::with_baseline(
+                self.text,
+                start + Point::new(lap, 0),
+                self.style.clone(),
+                Baseline::Top,
+            )
+            .draw(&mut clipped)?;
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,131 @@
+use chrono::{DateTime, Duration, NaiveDate, NaiveTime, TimeZone, Utc};
+use chrono_tz::Tz;
+use std::f64::consts::PI;
+
+/// When the sun rises and sets on a given day.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SunEvents {
+    Sun {
+        rise: DateTime<Utc>,
+        set: DateTime<Utc>,
+    },
+    /// Midnight sun, the sun stays above the horizon all day.
+    AlwaysUp,
+    /// Polar night, the sun never comes up.
+    AlwaysDown,
+}
+
+/// Sunrise and sunset on `date` at the given position, in degrees with north and east positive.
+///
+/// Uses the NOAA sunrise equation, which is good to a minute or two away from the poles.
+pub fn sun_events(date: NaiveDate, latitude: f64, longitude: f64) -> SunEvents {
+    let epoch = NaiveDate::from_ymd_opt(2000, 1, 1).unwrap();
+    let days = (date - epoch).num_days() as f64 + 0.0008;
+
+    // Mean solar noon, solar mean anomaly and equation of the center
+    let mean_noon = days - longitude / 360.0;
+    let anomaly = (357.5291 + 0.98560028 * mean_noon)
+        .rem_euclid(360.0)
+        .to_radians();
+    let center =
+        1.9148 * anomaly.sin() + 0.02 * (2.0 * anomaly).sin() + 0.0003 * (3.0 * anomaly).sin();
+
+    // Ecliptic longitude, solar transit and declination
+    let ecliptic = (anomaly.to_degrees() + center + 180.0 + 102.9372)
+        .rem_euclid(360.0)
+        .to_radians();
+    let transit = 2451545.0 + mean_noon + 0.0053 * anomaly.sin() - 0.0069 * (2.0 * ecliptic).sin();
+    let declination = (ecliptic.sin() * 23.4397_f64.to_radians().sin()).asin();
+
+    // Hour angle of the sun's upper edge on the horizon, corrected for refraction
+    let latitude = latitude.to_radians();
+    let cos_hour_angle = ((-0.833_f64).to_radians().sin() - latitude.sin() * declination.sin())
+        / (latitude.cos() * declination.cos());
+
+    if cos_hour_angle < -1.0 {
+        return SunEvents::AlwaysUp;
+    }
+    if cos_hour_angle > 1.0 {
+        return SunEvents::AlwaysDown;
+    }
+
+    let half_day = cos_hour_angle.acos() / (2.0 * PI);
+    SunEvents::Sun {
+        rise: from_julian(transit - half_day),
+        set: from_julian(transit + half_day),
+    }
+}
+
+fn from_julian(julian: f64) -> DateTime<Utc> {
+    let seconds = (julian - 2440587.5) * 86400.0;
+    Utc.timestamp_opt(seconds.round() as i64, 0).unwrap()
+}
+
+/// What the panel should be doing at a given moment.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Dimming {
+    /// Lit at this perceived brightness, 0-100%.
+    On(u8),
+    Off,
+}
+
+/// Dims the panel between day and night brightness around sunrise and sunset, and
+/// optionally turns it off overnight.
+pub struct DimmingSchedule {
+    latitude: f64,
+    longitude: f64,
+    day: u8,
+    night: u8,
+    ramp: Duration,
+    off: Option<(NaiveTime, NaiveTime)>,
+}
+
+impl DimmingSchedule {
+    /// Brightness ramps linearly from `night` to `day` over `ramp`, centred on sunrise,
+    /// and back again around sunset.
+    pub fn new(latitude: f64, longitude: f64, day: u8, night: u8, ramp: Duration) -> Self {
+        DimmingSchedule {
+            latitude,
+            longitude,
+            day,
+            night,
+            ramp,
+            off: None,
+        }
+    }
+
+    /// Turns the panel off from `from` until `until`, local time. The window may span midnight.
+    pub fn with_off_window(mut self, from: NaiveTime, until: NaiveTime) -> Self {
+        self.off = Some((from, until));
+        self
+    }
+
+    pub fn at(&self, time: DateTime<Tz>) -> Dimming {
+        if let Some((from, until)) = self.off {
+            let now = time.time();
+            let off = if from <= until {
+                from <= now && now < until
+            } else {
+                now >= from || now < until
+            };
+            if off {
+                return Dimming::Off;
+            }
+        }
+
+        let daylight = match sun_events(time.date_naive(), self.latitude, self.longitude) {
+            SunEvents::AlwaysUp => 1.0,
+            SunEvents::AlwaysDown => 0.0,
+            SunEvents::Sun { rise, set } => {
+                let time = time.with_timezone(&Utc);
+                let ramp = self.ramp.num_seconds().max(1) as f64;
+                let morning = (time - (rise - self.ramp / 2)).num_seconds() as f64 / ramp;
+                let evening = ((set + self.ramp / 2) - time).num_seconds() as f64 / ramp;
+                morning.min(evening).clamp(0.0, 1.0)
+            }
+        };
+
+        let range = self.day as f64 - self.night as f64;
+        Dimming::On((self.night as f64 + range * daylight).round() as u8)
+    }
+}
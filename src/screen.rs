@@ -0,0 +1,647 @@
+use crate::board::{self, text_width, HourFormat, CHARACTER_STYLE, LINE};
+use crate::departure::Departure;
+use crate::layout::Layout;
+use crate::marquee::Marquee;
+use chrono::{DateTime, TimeDelta, Timelike};
+use chrono_tz::Tz;
+use core::fmt;
+use core::time::Duration;
+use embedded_graphics::mono_font::MonoTextStyle;
+use embedded_graphics::pixelcolor::BinaryColor;
+use embedded_graphics::prelude::{DrawTarget, Point, Size};
+use embedded_graphics::primitives::Rectangle;
+use embedded_graphics::text::renderer::TextRenderer;
+use embedded_graphics::text::{Alignment, Baseline, Text};
+use embedded_graphics::Drawable;
+use matrix_fonts::{ProportionalTextStyle, FONT_3X5, FONT_DIGITS_16, WARNING};
+
+/// How often the departures are fetched again while they're on screen.
+const DEPARTURES_INTERVAL: TimeDelta = TimeDelta::seconds(20);
+/// How often the weather is fetched again while it's on screen.
+const WEATHER_INTERVAL: TimeDelta = TimeDelta::minutes(15);
+
+/// Up to three departures from a stop, soonest first.
+pub type Departures = [Option<Departure>; 3];
+
+/// One page of the rotation, drawn over the whole panel.
+pub trait Screen<D>
+where
+    D: DrawTarget<Color = BinaryColor>,
+{
+    /// How long the screen likes to stay up each time round, unless the config says
+    /// otherwise.
+    fn dwell(&self) -> Duration;
+
+    /// Called every frame while the screen is up, before drawing it, to fetch or work out
+    /// what it shows. Returns `true` when that changed enough to animate to.
+    fn update(&mut self, _now: DateTime<Tz>) -> bool {
+        false
+    }
+
+    /// Draws the screen as it stands `elapsed` after it came up, on a cleared target.
+    fn draw(&self, target: &mut D, now: DateTime<Tz>, elapsed: Duration) -> Result<(), D::Error>;
+}
+
+/// The screens the config can turn on.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ScreenKind {
+    Departures,
+    Clock,
+    Weather,
+    Message,
+}
+
+/// One entry of the `screens` list in the config.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Slot {
+    pub kind: ScreenKind,
+    /// How long to show it for, or `None` to leave it to the screen.
+    pub dwell: Option<Duration>,
+}
+
+/// Why the `screens` list in the config couldn't be read.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum RotationError {
+    UnknownScreen(String),
+    BadDwell(String),
+    /// Every screen was left out.
+    NoScreens,
+}
+
+impl fmt::Display for RotationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RotationError::UnknownScreen(name) => write!(f, "unknown screen \"{name}\""),
+            RotationError::BadDwell(dwell) => write!(f, "bad number of seconds \"{dwell}\""),
+            RotationError::NoScreens => write!(f, "no screens are enabled"),
+        }
+    }
+}
+
+impl std::error::Error for RotationError {}
+
+/// Reads the `screens` list from the config: screen names in the order to show them,
+/// each with an optional number of seconds after a colon, e.g.
+/// `"departures:30, clock:10, message"`. Screens left out are disabled.
+pub fn parse_rotation(spec: &str) -> Result<Vec<Slot>, RotationError> {
+    let mut slots = Vec::new();
+    for entry in spec
+        .split(',')
+        .map(str::trim)
+        .filter(|entry| !entry.is_empty())
+    {
+        let (name, seconds) = match entry.split_once(':') {
+            Some((name, seconds)) => (name.trim(), Some(seconds.trim())),
+            None => (entry, None),
+        };
+
+        let kind = match name {
+            "departures" => ScreenKind::Departures,
+            "clock" => ScreenKind::Clock,
+            "weather" => ScreenKind::Weather,
+            "message" => ScreenKind::Message,
+            _ => return Err(RotationError::UnknownScreen(name.to_string())),
+        };
+        let dwell = match seconds {
+            Some(seconds) => match seconds.parse() {
+                Ok(seconds) if seconds > 0 => Some(Duration::from_secs(seconds)),
+                _ => return Err(RotationError::BadDwell(seconds.to_string())),
+            },
+            None => None,
+        };
+
+        slots.push(Slot { kind, dwell });
+    }
+
+    if slots.is_empty() {
+        return Err(RotationError::NoScreens);
+    }
+    Ok(slots)
+}
+
+/// Shows each of its screens in turn, for as long as each one asks.
+pub struct Rotation<D> {
+    screens: Vec<(Box<dyn Screen<D>>, Option<Duration>)>,
+    current: usize,
+    /// When the current screen came up, counted from when the rotation started.
+    shown_at: Duration,
+}
+
+impl<D> Rotation<D>
+where
+    D: DrawTarget<Color = BinaryColor>,
+{
+    pub fn new() -> Self {
+        Rotation {
+            screens: Vec::new(),
+            current: 0,
+            shown_at: Duration::ZERO,
+        }
+    }
+
+    /// Adds `screen` to the end of the rotation, shown for `dwell` or as long as it likes.
+    pub fn with_screen(
+        mut self,
+        screen: impl Screen<D> + 'static,
+        dwell: Option<Duration>,
+    ) -> Self {
+        self.screens.push((Box::new(screen), dwell));
+        self
+    }
+
+    pub fn len(&self) -> usize {
+        self.screens.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.screens.is_empty()
+    }
+
+    /// Index of the screen up now.
+    pub fn index(&self) -> usize {
+        self.current
+    }
+
+    /// Moves on to the next screen once the current one has been up long enough, `uptime`
+    /// after the rotation started. Returns `true` if it did.
+    pub fn advance(&mut self, uptime: Duration) -> bool {
+        let Some((screen, dwell)) = self.screens.get(self.current) else {
+            return false;
+        };
+        if self.screens.len() < 2 || self.elapsed(uptime) < dwell.unwrap_or(screen.dwell()) {
+            return false;
+        }
+
+        self.current = (self.current + 1) % self.screens.len();
+        self.shown_at = uptime;
+        true
+    }
+
+    /// How long the current screen has been up, `uptime` after the rotation started.
+    pub fn elapsed(&self, uptime: Duration) -> Duration {
+        uptime.saturating_sub(self.shown_at)
+    }
+
+    /// The screen up now, or `None` if there are no screens.
+    pub fn current(&mut self) -> Option<&mut dyn Screen<D>> {
+        self.screens
+            .get_mut(self.current)
+            .map(|(screen, _)| screen.as_mut() as &mut dyn Screen<D>)
+    }
+}
+
+impl<D> Default for Rotation<D>
+where
+    D: DrawTarget<Color = BinaryColor>,
+{
+    fn default() -> Self {
+        Rotation::new()
+    }
+}
+
+/// `lines` as they fit on a panel `height` pixels high: one to a row if there's room,
+/// or all on one row otherwise.
+fn fit_lines(lines: &[&str], height: u32) -> Vec<String> {
+    if lines.len() as u32 * LINE <= height {
+        lines.iter().map(|line| line.to_string()).collect()
+    } else {
+        vec![lines.join("  ")]
+    }
+}
+
+/// Draws `lines` one under the other in the middle of the target, each centered and
+/// scrolling at `speed` pixels a second if it doesn't fit.
+fn draw_lines<D>(
+    target: &mut D,
+    lines: &[String],
+    elapsed: Duration,
+    speed: u32,
+) -> Result<(), D::Error>
+where
+    D: DrawTarget<Color = BinaryColor>,
+{
+    let bounds = target.bounding_box();
+    let top = (bounds.size.height.saturating_sub(lines.len() as u32 * LINE) / 2) as i32;
+
+    for (index, line) in lines.iter().enumerate() {
+        let area = Rectangle::new(
+            bounds.top_left + Point::new(0, top + (index as u32 * LINE) as i32),
+            Size::new(bounds.size.width, LINE),
+        );
+        Marquee::new(line, CHARACTER_STYLE, area)
+            .with_alignment(Alignment::Center)
+            .with_speed(speed)
+            .draw(target, elapsed)?;
+    }
+
+    Ok(())
+}
+
+/// The departure board: a row of departures per stop, the clock and a status bar.
+pub struct DeparturesScreen<F> {
+    layout: Layout,
+    source: F,
+    rows: Vec<(String, Departures)>,
+    /// How long it takes to walk to each row's stop.
+    walks: Vec<TimeDelta>,
+    status: Option<String>,
+    fetched: Option<DateTime<Tz>>,
+    speed: u32,
+    hours: HourFormat,
+}
+
+impl<F> DeparturesScreen<F>
+where
+    F: FnMut(DateTime<Tz>) -> Option<Vec<(String, Departures)>>,
+{
+    /// Draws the rows `source` comes back with into the zones of `layout`, made by
+    /// [`board::layout`]. `source` is asked again every 20 seconds while the board is up,
+    /// and returns `None` when the departures can't be had.
+    pub fn new(layout: Layout, source: F) -> Self {
+        DeparturesScreen {
+            layout,
+            source,
+            rows: Vec::new(),
+            walks: Vec::new(),
+            status: None,
+            fetched: None,
+            speed: 20,
+            hours: HourFormat::TwentyFour,
+        }
+    }
+
+    /// Sets how fast rows that don't fit scroll, in pixels per second.
+    pub fn with_speed(mut self, speed: u32) -> Self {
+        self.speed = speed;
+        self
+    }
+
+    /// Sets how the clock in the corner counts the hours.
+    pub fn with_hour_format(mut self, hours: HourFormat) -> Self {
+        self.hours = hours;
+        self
+    }
+
+    /// Sets how long it takes to walk to each row's stop, top row first, so departures
+    /// can say when it's time to leave for them and drop off once it's too late.
+    pub fn with_walk_times(mut self, walks: Vec<TimeDelta>) -> Self {
+        self.walks = walks;
+        self
+    }
+}
+
+impl<D, F> Screen<D> for DeparturesScreen<F>
+where
+    D: DrawTarget<Color = BinaryColor>,
+    F: FnMut(DateTime<Tz>) -> Option<Vec<(String, Departures)>>,
+{
+    fn dwell(&self) -> Duration {
+        Duration::from_secs(30)
+    }
+
+    fn update(&mut self, now: DateTime<Tz>) -> bool {
+        if self
+            .fetched
+            .is_some_and(|at| now - at < DEPARTURES_INTERVAL)
+        {
+            return false;
+        }
+
+        // Keep showing the last departures until every stop comes back
+        match (self.source)(now) {
+            Some(rows) => {
+                self.rows = rows;
+                self.status = None;
+            }
+            None => self.status = Some(format!("{WARNING} Departures unavailable")),
+        }
+        self.fetched = Some(now);
+        true
+    }
+
+    fn draw(&self, target: &mut D, now: DateTime<Tz>, elapsed: Duration) -> Result<(), D::Error> {
+        for (index, (prefix, departures)) in self.rows.iter().enumerate() {
+            let Some(zone) = self.layout.zone(&board::row_zone(index)) else {
+                continue;
+            };
+            let walk = self.walks.get(index).copied().unwrap_or_default();
+            let names = board::format_departure_names(departures, walk, now);
+            board::draw_scrolling_departures(target, prefix, &names, zone, elapsed, self.speed)?;
+        }
+
+        if let Some(zone) = self.layout.zone(board::CLOCK) {
+            board::draw_clock(target, zone, now, self.hours)?;
+        }
+
+        if let (Some(zone), Some(status)) = (self.layout.zone(board::STATUS), &self.status) {
+            board::draw_status(target, zone, status, elapsed, self.speed)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Digits two modules tall for the clock screen.
+const BIG_DIGITS: ProportionalTextStyle<'static, BinaryColor> =
+    ProportionalTextStyle::new(&FONT_DIGITS_16, BinaryColor::On).with_tabular_digits(true);
+
+/// The time in digits two modules tall, with AM or PM beside it on a 12-hour clock and
+/// the date underneath when there's room. Panels too short for the digits get the time
+/// in ordinary text.
+#[derive(Clone, Copy, Debug)]
+pub struct ClockScreen {
+    timezone: Tz,
+    hours: HourFormat,
+    blink: bool,
+    date: bool,
+}
+
+impl ClockScreen {
+    /// A 24-hour clock showing the time in `timezone`, with a blinking colon and the date.
+    pub fn new(timezone: Tz) -> Self {
+        ClockScreen {
+            timezone,
+            hours: HourFormat::TwentyFour,
+            blink: true,
+            date: true,
+        }
+    }
+
+    pub fn with_hour_format(mut self, hours: HourFormat) -> Self {
+        self.hours = hours;
+        self
+    }
+
+    /// Sets whether the colon blinks, lit for the first half of every second.
+    pub fn with_blinking_colon(mut self, blink: bool) -> Self {
+        self.blink = blink;
+        self
+    }
+
+    /// Sets whether the date goes under the time.
+    pub fn with_date(mut self, date: bool) -> Self {
+        self.date = date;
+        self
+    }
+}
+
+/// Draws `hours` and `minutes` with a colon between them at `top_left`, blanking the
+/// colon out again unless `colon` is set so the minutes don't move when it blinks.
+/// Returns how wide the time is.
+fn draw_time<D, S>(
+    target: &mut D,
+    style: S,
+    hours: u32,
+    minutes: u32,
+    top_left: Point,
+    colon: bool,
+) -> Result<u32, D::Error>
+where
+    D: DrawTarget<Color = BinaryColor>,
+    S: TextRenderer<Color = BinaryColor> + Clone,
+{
+    let text = format!("{hours}:{minutes:02}");
+    Text::with_baseline(&text, top_left, style.clone(), Baseline::Top).draw(target)?;
+
+    if !colon {
+        let hours = text.find(':').unwrap_or_default();
+        let start = style.measure_string(&text[..hours], top_left, Baseline::Top);
+        let end = style.measure_string(&text[..=hours], top_left, Baseline::Top);
+        let width = (end.next_position.x - start.next_position.x) as u32;
+        target.fill_solid(
+            &Rectangle::new(start.next_position, Size::new(width, style.line_height())),
+            BinaryColor::Off,
+        )?;
+    }
+
+    Ok(style
+        .measure_string(&text, top_left, Baseline::Top)
+        .bounding_box
+        .size
+        .width)
+}
+
+impl<D> Screen<D> for ClockScreen
+where
+    D: DrawTarget<Color = BinaryColor>,
+{
+    fn dwell(&self) -> Duration {
+        Duration::from_secs(10)
+    }
+
+    fn draw(&self, target: &mut D, now: DateTime<Tz>, _elapsed: Duration) -> Result<(), D::Error> {
+        let now = now.with_timezone(&self.timezone);
+        let bounds = target.bounding_box();
+        let colon = !self.blink || now.timestamp_subsec_millis() < 500;
+        let (hours, minutes) = (self.hours.hour(&now), now.minute());
+        let meridiem = match self.hours {
+            HourFormat::Twelve if now.hour() < 12 => Some("AM"),
+            HourFormat::Twelve => Some("PM"),
+            HourFormat::TwentyFour => None,
+        };
+
+        // Not enough rows for the big digits, so just the time, and AM or PM after it
+        if bounds.size.height < FONT_DIGITS_16.height {
+            let mut line = format!("{hours}:{minutes:02}");
+            if let Some(meridiem) = meridiem {
+                line = format!("{line} {meridiem}");
+            }
+            let top_left = bounds.top_left
+                + Point::new(
+                    (bounds.size.width.saturating_sub(text_width(&line)) / 2) as i32,
+                    (bounds.size.height.saturating_sub(LINE) / 2) as i32,
+                );
+            let width = draw_time(target, CHARACTER_STYLE, hours, minutes, top_left, colon)?;
+            if let Some(meridiem) = meridiem {
+                let at = top_left + Point::new((width + text_width(" ")) as i32, 0);
+                Text::with_baseline(meridiem, at, CHARACTER_STYLE, Baseline::Top).draw(target)?;
+            }
+            return Ok(());
+        }
+
+        let small = MonoTextStyle::new(&FONT_3X5, BinaryColor::On);
+        let time = format!("{hours}:{minutes:02}");
+        let time_width = BIG_DIGITS
+            .measure_string(&time, Point::zero(), Baseline::Top)
+            .bounding_box
+            .size
+            .width;
+        let meridiem_width = meridiem.map_or(0, |meridiem| {
+            2 + small
+                .measure_string(meridiem, Point::zero(), Baseline::Top)
+                .bounding_box
+                .size
+                .width
+        });
+        let date = (self.date && bounds.size.height >= FONT_DIGITS_16.height + LINE)
+            .then(|| now.format("%a %-d %b").to_string());
+        let height = FONT_DIGITS_16.height + date.as_ref().map_or(0, |_| LINE);
+
+        let top_left = bounds.top_left
+            + Point::new(
+                (bounds
+                    .size
+                    .width
+                    .saturating_sub(time_width + meridiem_width)
+                    / 2) as i32,
+                (bounds.size.height.saturating_sub(height) / 2) as i32,
+            );
+        draw_time(target, BIG_DIGITS, hours, minutes, top_left, colon)?;
+
+        if let Some(meridiem) = meridiem {
+            let at = top_left + Point::new(time_width as i32 + 2, 0);
+            Text::with_baseline(meridiem, at, small, Baseline::Top).draw(target)?;
+        }
+
+        if let Some(date) = date {
+            let at = Point::new(
+                bounds.top_left.x
+                    + (bounds.size.width.saturating_sub(text_width(&date)) / 2) as i32,
+                top_left.y + FONT_DIGITS_16.height as i32,
+            );
+            Text::with_baseline(&date, at, CHARACTER_STYLE, Baseline::Top).draw(target)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// The weather now, as the screen shows it.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Weather {
+    /// In degrees Celsius.
+    pub temperature: f32,
+    /// WMO weather interpretation code, as Open-Meteo and most forecasts give it.
+    pub code: u8,
+}
+
+impl Weather {
+    /// A word or two for the weather code.
+    pub fn description(&self) -> &'static str {
+        match self.code {
+            0 => "Clear",
+            1 | 2 => "Partly cloudy",
+            3 => "Cloudy",
+            45 | 48 => "Fog",
+            51..=57 => "Drizzle",
+            61..=67 | 80..=82 => "Rain",
+            71..=77 | 85 | 86 => "Snow",
+            95..=99 => "Thunder",
+            _ => "Unknown",
+        }
+    }
+}
+
+/// The temperature and what the sky is doing.
+pub struct WeatherScreen<F> {
+    source: F,
+    weather: Option<Weather>,
+    fetched: Option<DateTime<Tz>>,
+    speed: u32,
+}
+
+impl<F> WeatherScreen<F>
+where
+    F: FnMut() -> Option<Weather>,
+{
+    /// Shows what `source` comes back with, asking again every 15 minutes while the
+    /// screen is up.
+    pub fn new(source: F) -> Self {
+        WeatherScreen {
+            source,
+            weather: None,
+            fetched: None,
+            speed: 20,
+        }
+    }
+
+    /// Sets how fast a line that doesn't fit scrolls, in pixels per second.
+    pub fn with_speed(mut self, speed: u32) -> Self {
+        self.speed = speed;
+        self
+    }
+}
+
+impl<D, F> Screen<D> for WeatherScreen<F>
+where
+    D: DrawTarget<Color = BinaryColor>,
+    F: FnMut() -> Option<Weather>,
+{
+    fn dwell(&self) -> Duration {
+        Duration::from_secs(10)
+    }
+
+    fn update(&mut self, now: DateTime<Tz>) -> bool {
+        if self.fetched.is_some_and(|at| now - at < WEATHER_INTERVAL) {
+            return false;
+        }
+
+        // A failed fetch keeps the last weather up, and waits as long as a good one to be
+        // tried again
+        let weather = (self.source)();
+        if weather.is_some() {
+            self.weather = weather;
+        }
+        self.fetched = Some(now);
+        weather.is_some()
+    }
+
+    fn draw(&self, target: &mut D, _now: DateTime<Tz>, elapsed: Duration) -> Result<(), D::Error> {
+        let height = target.bounding_box().size.height;
+        let lines = match self.weather {
+            Some(weather) => {
+                let temperature = format!("{:.0}\u{b0}C", weather.temperature);
+                fit_lines(&[&temperature, weather.description()], height)
+            }
+            None => fit_lines(&[&format!("{WARNING} Weather unavailable")], height),
+        };
+        draw_lines(target, &lines, elapsed, self.speed)
+    }
+}
+
+/// A message from the config, a line to a row, scrolling if a line doesn't fit.
+pub struct MessageScreen {
+    lines: Vec<String>,
+    bounds: Rectangle,
+    speed: u32,
+}
+
+impl MessageScreen {
+    /// Shows `text` on a panel covering `bounds`, with a line break between each row.
+    pub fn new(text: &str, bounds: Rectangle) -> Self {
+        let lines: Vec<&str> = text.lines().collect();
+        MessageScreen {
+            lines: fit_lines(&lines, bounds.size.height),
+            bounds,
+            speed: 20,
+        }
+    }
+
+    /// Sets how fast lines that don't fit scroll, in pixels per second.
+    pub fn with_speed(mut self, speed: u32) -> Self {
+        self.speed = speed;
+        self
+    }
+}
+
+impl<D> Screen<D> for MessageScreen
+where
+    D: DrawTarget<Color = BinaryColor>,
+{
+    /// Long enough to read, and for the longest line to scroll all the way round.
+    fn dwell(&self) -> Duration {
+        let area = Rectangle::new(Point::zero(), Size::new(self.bounds.size.width, LINE));
+        self.lines
+            .iter()
+            .map(|line| {
+                Marquee::new(line, CHARACTER_STYLE, area)
+                    .with_speed(self.speed)
+                    .lap()
+            })
+            .fold(Duration::from_secs(10), Duration::max)
+    }
+
+    fn draw(&self, target: &mut D, _now: DateTime<Tz>, elapsed: Duration) -> Result<(), D::Error> {
+        draw_lines(target, &self.lines, elapsed, self.speed)
+    }
+}
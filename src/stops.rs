@@ -0,0 +1,123 @@
+use crate::filter::Filter;
+use chrono::TimeDelta;
+use core::fmt;
+
+/// A stop to ask the departures API about, and where its row goes on the board.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Stop {
+    /// The stop's ID in the National Public Transport Access Nodes, e.g. "8220IR3881".
+    pub id: String,
+    /// The name the journey planner gives the stop, e.g. "Killester".
+    pub name: String,
+    /// What the API calls the kind of stop, e.g. "TRAIN_STATION" or "BUS_STOP".
+    pub stop_type: String,
+    /// Shown at the start of the stop's row, e.g. "KI".
+    pub label: String,
+    /// Which row of the board the stop goes on, counting from 0 at the top.
+    pub row: usize,
+    /// How long it takes to walk to the stop. Departures are asked for from this far
+    /// ahead, and unless the filter has a `cutoff` of its own, sooner ones are hidden.
+    pub walk: TimeDelta,
+    /// Which of its departures are shown.
+    pub filter: Filter,
+}
+
+/// Why the `stops` list couldn't be read.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum StopError {
+    /// An entry without an ID, name, type and label.
+    MissingField(String),
+    BadRow(String),
+    /// A `key=value` filter option that isn't one.
+    BadOption(String),
+    /// Two stops were put on the same row.
+    SameRow(usize),
+    NoStops,
+}
+
+impl fmt::Display for StopError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            StopError::MissingField(entry) => {
+                write!(f, "\"{entry}\" needs an ID, name, type and label")
+            }
+            StopError::BadRow(row) => write!(f, "bad row \"{row}\""),
+            StopError::BadOption(option) => write!(f, "bad filter option \"{option}\""),
+            StopError::SameRow(row) => write!(f, "more than one stop on row {row}"),
+            StopError::NoStops => write!(f, "no stops are configured"),
+        }
+    }
+}
+
+impl std::error::Error for StopError {}
+
+/// Reads the `stops` list: one stop per line or between semicolons, its ID, name, type,
+/// label and optional row separated by `|`, then any filter options as `key=value`, e.g.
+/// `"8220IR3881 | Killester | TRAIN_STATION | KI | 0 | walk=9 | to=Greystones"`. `walk` is
+/// the minutes it takes to get to the stop; see [`Filter::set`] for the other options.
+/// Stops without a row go on the board in the order they're listed. Rows have to be
+/// different and leave no gaps, and the stops come back in row order.
+pub fn parse_stops(spec: &str) -> Result<Vec<Stop>, StopError> {
+    let entries: Vec<&str> = spec
+        .split(['\n', ';'])
+        .map(str::trim)
+        .filter(|entry| !entry.is_empty())
+        .collect();
+
+    let mut stops = Vec::with_capacity(entries.len());
+    for (position, entry) in entries.iter().enumerate() {
+        let mut fields = entry.split('|').map(str::trim);
+        let mut field = || {
+            fields
+                .next()
+                .filter(|field| !field.is_empty())
+                .map(String::from)
+                .ok_or_else(|| StopError::MissingField(entry.to_string()))
+        };
+        let (id, name, stop_type, label) = (field()?, field()?, field()?, field()?);
+
+        let (mut row, mut walk, mut filter) = (None, None, Filter::default());
+        let mut cutoff = false;
+        for field in fields {
+            match field.split_once('=') {
+                Some((key, value)) => match (key.trim(), value.trim().parse()) {
+                    ("walk", Ok(minutes)) if minutes >= 0 => {
+                        walk = Some(TimeDelta::minutes(minutes))
+                    }
+                    (key, _) if key != "walk" && filter.set(key, value.trim()) => {
+                        cutoff |= key == "cutoff";
+                    }
+                    _ => return Err(StopError::BadOption(field.to_string())),
+                },
+                None => match field.parse() {
+                    Ok(number) if number < entries.len() && row.is_none() => row = Some(number),
+                    _ => return Err(StopError::BadRow(field.to_string())),
+                },
+            }
+        }
+
+        let walk = walk.unwrap_or_default();
+        if !cutoff {
+            filter.cutoff = walk;
+        }
+
+        stops.push(Stop {
+            id,
+            name,
+            stop_type,
+            label,
+            row: row.unwrap_or(position),
+            walk,
+            filter,
+        });
+    }
+
+    if stops.is_empty() {
+        return Err(StopError::NoStops);
+    }
+    stops.sort_by_key(|stop| stop.row);
+    if let Some(pair) = stops.windows(2).find(|pair| pair[0].row == pair[1].row) {
+        return Err(StopError::SameRow(pair[0].row));
+    }
+    Ok(stops)
+}
@@ -0,0 +1,143 @@
+use chrono::{DateTime, Datelike, NaiveDate, Offset, TimeDelta, TimeZone, Timelike, Utc};
+use chrono_tz::{OffsetName, Tz};
+
+/// The POSIX `TZ` string for `timezone` as its rules stand in `year`, for the C library's
+/// `localtime` to follow the same zone as chrono, e.g. `"GMT0IST,M3.5.0/1,M10.5.0"` for
+/// Europe/Dublin.
+///
+/// POSIX can only describe one standard and one daylight saving offset, so this is worked
+/// out from the changes in `year` and needs making again if the rules change. The lower of
+/// the two offsets is taken as standard time.
+pub fn posix_tz(timezone: Tz, year: i32) -> String {
+    let start = Utc.with_ymd_and_hms(year, 1, 1, 0, 0, 0).unwrap();
+    let days = NaiveDate::from_ymd_opt(year, 12, 31).unwrap().ordinal() as i64;
+
+    // Look for the day each change happens on, then narrow it down to the second
+    let mut changes = Vec::new();
+    for day in 0..days {
+        let (from, to) = (
+            start + TimeDelta::days(day),
+            start + TimeDelta::days(day + 1),
+        );
+        if utc_offset(timezone, from) != utc_offset(timezone, to) {
+            changes.push(change_between(timezone, from, to));
+        }
+    }
+
+    let (first, second) = match changes[..] {
+        [first, second] => (first, second),
+        // No daylight saving time, or rules POSIX can't describe: the offset at the start of
+        // the year for the whole of it
+        _ => {
+            return format!(
+                "{}{}",
+                name(timezone, start),
+                posix_offset(utc_offset(timezone, start))
+            )
+        }
+    };
+
+    // The change into the higher offset starts daylight saving time
+    let (forward, back) = match utc_offset(timezone, first) > utc_offset(timezone, second) {
+        true => (first, second),
+        false => (second, first),
+    };
+    let (standard, daylight) = (utc_offset(timezone, back), utc_offset(timezone, forward));
+
+    let mut tz = format!(
+        "{}{}{}",
+        name(timezone, back),
+        posix_offset(standard),
+        name(timezone, forward)
+    );
+    if daylight - standard != 3600 {
+        tz.push_str(&posix_offset(daylight));
+    }
+    tz.push_str(&rule(forward - TimeDelta::seconds(1), standard));
+    tz.push_str(&rule(back - TimeDelta::seconds(1), daylight));
+    tz
+}
+
+/// Seconds east of UTC at `at`.
+fn utc_offset(timezone: Tz, at: DateTime<Utc>) -> i32 {
+    timezone
+        .offset_from_utc_datetime(&at.naive_utc())
+        .fix()
+        .local_minus_utc()
+}
+
+/// The first second with the new offset, somewhere after `from` and up to `to`.
+fn change_between(timezone: Tz, mut from: DateTime<Utc>, mut to: DateTime<Utc>) -> DateTime<Utc> {
+    let before = utc_offset(timezone, from);
+    while to - from > TimeDelta::seconds(1) {
+        let middle = from + (to - from) / 2;
+        if utc_offset(timezone, middle) == before {
+            from = middle;
+        } else {
+            to = middle;
+        }
+    }
+    to
+}
+
+/// The abbreviation in use at `at`, quoted in angle brackets unless it's all letters. Zones
+/// without one, which chrono-tz leaves out, get their offset like "<+0530>" instead.
+fn name(timezone: Tz, at: DateTime<Utc>) -> String {
+    let offset = timezone.offset_from_utc_datetime(&at.naive_utc());
+    match offset.abbreviation() {
+        Some(name) if name.len() >= 3 && name.chars().all(|c| c.is_ascii_alphabetic()) => {
+            name.to_string()
+        }
+        Some(name) => format!("<{name}>"),
+        None => {
+            let seconds = offset.fix().local_minus_utc();
+            let sign = if seconds < 0 { '-' } else { '+' };
+            match (seconds.abs() / 3600, seconds.abs() / 60 % 60) {
+                (hours, 0) => format!("<{sign}{hours:02}>"),
+                (hours, minutes) => format!("<{sign}{hours:02}{minutes:02}>"),
+            }
+        }
+    }
+}
+
+/// POSIX counts offsets west of UTC, so UTC+1 is "-1".
+fn posix_offset(seconds_east: i32) -> String {
+    let sign = if seconds_east > 0 { "-" } else { "" };
+    format!("{sign}{}", clock_time(seconds_east.unsigned_abs()))
+}
+
+/// Hours, with minutes and seconds only if there are any.
+fn clock_time(seconds: u32) -> String {
+    match (seconds / 3600, seconds / 60 % 60, seconds % 60) {
+        (hours, 0, 0) => format!("{hours}"),
+        (hours, minutes, 0) => format!("{hours}:{minutes:02}"),
+        (hours, minutes, seconds) => format!("{hours}:{minutes:02}:{seconds:02}"),
+    }
+}
+
+/// The `,Mm.w.d/time` rule for a change made just after `last_second`, in local time at
+/// `offset`. A change in the last seven days of a month counts as the last of its weekday.
+fn rule(last_second: DateTime<Utc>, offset: i32) -> String {
+    let local = last_second.naive_utc() + TimeDelta::seconds(offset as i64 + 1);
+    let date = local.date();
+    let days_in_month = NaiveDate::from_ymd_opt(date.year(), date.month(), 1)
+        .and_then(|first| first.checked_add_months(chrono::Months::new(1)))
+        .map_or(31, |next| next.pred_opt().unwrap().day());
+    let week = match date.day() {
+        day if day + 7 > days_in_month => 5,
+        day => (day - 1) / 7 + 1,
+    };
+
+    let mut rule = format!(
+        ",M{}.{}.{}",
+        date.month(),
+        week,
+        date.weekday().num_days_from_sunday()
+    );
+    // 2am is the default
+    let time = local.time().num_seconds_from_midnight();
+    if time != 2 * 3600 {
+        rule.push_str(&format!("/{}", clock_time(time)));
+    }
+    rule
+}
@@ -1,5 +1,5 @@
 use anyhow::{bail, Result};
-use chrono::{DateTime, SecondsFormat};
+use chrono::{DateTime, SecondsFormat, Utc};
 use chrono_tz::Tz;
 use core::str;
 use std::ptr::{self, null_mut};
@@ -10,6 +10,12 @@ use esp_idf_svc::{
 use serde_json::{json, Value};
 
 use log::{error, info};
+
+use crate::departure::Departure;
+use crate::json::ArrayReader;
+use crate::screen::{Departures, Weather};
+use crate::stops::Stop;
+
 pub fn wifi(
     ssid: &str,
     pass: &str,
@@ -107,33 +113,17 @@ pub fn wifi(
     Ok(Box::new(esp_wifi))
 }
 
-#[derive(Clone,Copy)]
-pub enum Location {
-    Killester,
-    CastleGrove,
-    CollinsAvenue,
-}
-
-impl Location {
-    fn stop_params(&self) -> (&'static str, &'static str, &'static str) {
-        match self {
-            Location::Killester => ("8220IR3881", "Killester", "TRAIN_STATION"),
-            Location::CastleGrove => ("8220DB000609", "Castle Grove, Clontarf", "BUS_STOP"),
-            Location::CollinsAvenue => ("8220DB000529", "Collins Avenue, Killester", "BUS_STOP"),
-        }
-    }
-}
-
 const URL: &str = "https://api-lts.transportforireland.ie/lts/lts/v1/public/departures";
+/// Departures read from each response for the stop's filter to choose from.
+const CANDIDATES: usize = 10;
 
 
 pub fn post_with_time(
     api_key: &str,
     departure_time: DateTime<Tz>,
-    location: Location,
-) -> Result<[Option<(String, DateTime<Tz>)>; 3]> {
-    // Get location-specific parameters
-    let (stop_id, stop_name, stop_type) = location.stop_params();
+    stop: &Stop,
+) -> Result<Departures> {
+    let timezone = departure_time.timezone();
 
     // Create a new EspHttpClient
     let connection = EspHttpConnection::new(&Configuration {
@@ -150,9 +140,9 @@ pub fn post_with_time(
     let request_body = json!({
         "departureDate": departure_time,
         "departureTime": departure_time,
-        "stopIds": [stop_id],
-        "stopName": stop_name,
-        "stopType": stop_type,
+        "stopIds": [stop.id],
+        "stopName": stop.name,
+        "stopType": stop.stop_type,
         "departureOrArrival": "DEPARTURE",
     });
 
@@ -189,73 +179,20 @@ pub fn post_with_time(
 
     match status {
         200..=299 => {
-            let mut response_body = String::new();
-            let mut occurrences = 0;
-            let target_str = format!("\"stopRef\":\"{}\"", stop_id);  // Use dynamic stopRef
-            let mut buf = [0; 256];
-
-            // Read data in chunks of 256 bytes
-            while response_body.match_indices(&target_str).count() < 3 {
-                occurrences = 0; // Reset occurrences in each chunk read
-                let bytes_read = response.read(&mut buf)?;
-                if bytes_read == 0 {
-                    break; // End of response
-                }
-
-                // Append the chunk to our growing response body
-                response_body.push_str(&String::from_utf8_lossy(&buf[..bytes_read]));
-
-                // Count occurrences of the target string in the accumulated response
-                for (index, _) in response_body.match_indices(&target_str) {
-                    occurrences += 1;
-                    if occurrences == 3 {
-                        response_body.truncate(index + target_str.len());
-                        break;
-                    }
-                }
-            }
-
-            if occurrences < 3 {
-                log::error!("Less than three occurrences of the target string were found");
-            }
-
-            // Close JSON after the third occurrence
-            let truncated_response = format!("{} }}]}}", response_body);
-
-            let v: Value = serde_json::from_str(&truncated_response)
+            // Only the departures the filter chooses from are kept, the rest of the body
+            // isn't even read
+            let departures = ArrayReader::new("stopDepartures")
+                .with_max_items(CANDIDATES)
+                .read::<Departure, _>(|buf| response.read(buf))
                 .map_err(|e| anyhow::anyhow!("Failed to parse JSON response: {}", e))?;
-    
-            // Extract stopDepartures
-            let departures = v["stopDepartures"]
-                .as_array()
-                .ok_or_else(|| anyhow::anyhow!("Failed to retrieve stop departures array"))?;
-
-            // Collect up to three scheduled departure times and service numbers
-            let mut schedule_times = [None, None, None];
-            for (i, departure) in departures.iter().enumerate().take(3) {
-                let service_number_key= {
-                   match location {
-                       Location::Killester => {"destination"},
-                       _ => {"serviceNumber"},
-                   }
-                };
-                if let (Some(service_number), Some(scheduled_departure)) = (
-                    departure[service_number_key].as_str(),
-                    departure["realTimeDeparture"].as_str(),
-                ) {
-                    departure["destination"].as_str();
-                    // Attempt to parse the scheduled departure string to DateTime
-                    match DateTime::parse_from_rfc3339(scheduled_departure) {
-                        Ok(parsed_time) => {
-                            schedule_times[i] = Some((service_number.to_string(), parsed_time.with_timezone(&chrono_tz::Tz::Europe__Dublin)));
-                        }
-                        Err(e) => log::error!("Failed to parse DateTime: {}", e),
-                    }
-                }
-            }
 
-            // Return the array of Option<(String, DateTime)>
-            Ok(schedule_times)
+            // Keep the departures the filter chooses, delays and cancellations and all
+            let now = Utc::now().with_timezone(&timezone);
+            let mut kept: Departures = [None, None, None];
+            for (slot, departure) in kept.iter_mut().zip(stop.filter.apply(&departures, now)) {
+                *slot = Some(departure.clone());
+            }
+            Ok(kept)
         }
         _ => {
             log::error!("Unexpected response code: {}", status);
@@ -263,3 +200,60 @@ pub fn post_with_time(
         }
     }
 }
+
+/// Largest file `download` takes, to leave the heap for everything else.
+const MAX_DOWNLOAD: usize = 64 * 1024;
+
+/// Fetches `url` with a GET request, for files like images that are uploaded somewhere
+/// instead of flashed with the firmware.
+pub fn download(url: &str) -> Result<Vec<u8>> {
+    let connection = EspHttpConnection::new(&Configuration {
+        use_global_ca_store: true,
+        crt_bundle_attach: Some(esp_idf_svc::sys::esp_crt_bundle_attach),
+        ..Default::default()
+    })?;
+    let mut client = Client::wrap(connection);
+
+    let mut response = client.get(url)?.submit()?;
+    let status = response.status();
+    if !(200..=299).contains(&status) {
+        bail!("Unexpected response code: {}", status);
+    }
+
+    let mut body = Vec::new();
+    let mut buf = [0; 256];
+    loop {
+        let bytes_read = response.read(&mut buf)?;
+        if bytes_read == 0 {
+            break;
+        }
+        if body.len() + bytes_read > MAX_DOWNLOAD {
+            bail!("{} is larger than {} bytes", url, MAX_DOWNLOAD);
+        }
+        body.extend_from_slice(&buf[..bytes_read]);
+    }
+
+    Ok(body)
+}
+
+/// The weather now at the given position, from Open-Meteo, which needs no API key.
+pub fn weather(latitude: f64, longitude: f64) -> Result<Weather> {
+    let url = format!(
+        "https://api.open-meteo.com/v1/forecast?latitude={}&longitude={}&current=temperature_2m,weather_code",
+        latitude, longitude
+    );
+    let body = download(&url)?;
+    let v: Value = serde_json::from_slice(&body)
+        .map_err(|e| anyhow::anyhow!("Failed to parse JSON response: {}", e))?;
+
+    match (
+        v["current"]["temperature_2m"].as_f64(),
+        v["current"]["weather_code"].as_u64(),
+    ) {
+        (Some(temperature), Some(code)) => Ok(Weather {
+            temperature: temperature as f32,
+            code: code as u8,
+        }),
+        _ => bail!("Failed to retrieve the current weather"),
+    }
+}